@@ -0,0 +1,23 @@
+// generates the C header for the C ABI (the capi feature) into OUT_DIR and checks the committed
+// include/aoc2022.h against it; with AOC2022_UPDATE_HEADER set (`make -C capi header`), the
+// committed one is overwritten instead
+fn main() {
+    #[cfg(feature = "capi")]
+    {
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let generated = format!("{}/aoc2022.h", std::env::var("OUT_DIR").unwrap());
+        let committed = format!("{}/include/aoc2022.h", dir);
+        println!("cargo:rerun-if-changed=src/common/capi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        println!("cargo:rerun-if-changed=include/aoc2022.h");
+        println!("cargo:rerun-if-env-changed=AOC2022_UPDATE_HEADER");
+        cbindgen::generate(&dir)
+            .expect("could not generate the C header")
+            .write_to_file(&generated);
+        if std::env::var_os("AOC2022_UPDATE_HEADER").is_some() {
+            std::fs::copy(&generated, &committed).expect("could not update include/aoc2022.h");
+        } else if std::fs::read(&generated).ok() != std::fs::read(&committed).ok() {
+            println!("cargo:warning=include/aoc2022.h is out of date, regenerate it with `make -C capi header`");
+        }
+    }
+}
@@ -0,0 +1,62 @@
+/* Smoke test for the C ABI: solves the examples through libaoc2022 and checks the status codes.
+ * Run it with `make -C capi`. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "aoc2022.h"
+
+static int failures = 0;
+
+static char *read_file(const char *path, size_t *len) {
+    FILE *f = fopen(path, "rb");
+    if (f == NULL) {
+        perror(path);
+        exit(1);
+    }
+    fseek(f, 0, SEEK_END);
+    *len = (size_t)ftell(f);
+    rewind(f);
+    char *data = malloc(*len);
+    if (fread(data, 1, *len, f) != *len) {
+        perror(path);
+        exit(1);
+    }
+    fclose(f);
+    return data;
+}
+
+static void check(uint32_t year, uint32_t day, uint32_t part, const char *input, size_t len,
+                  AocStatus expected_status, const char *expected) {
+    char *out = NULL;
+    AocStatus status = aoc_solve(year, day, part, (const uint8_t *)input, len, &out);
+    int ok = status == expected_status && (expected == NULL || strcmp(out, expected) == 0);
+    printf("%s %u day %u part %u: status %d, %s\n", ok ? "ok  " : "FAIL", year, day, part, status,
+           out ? out : "(no output)");
+    failures += !ok;
+    /* strings from the library go back to the library, never to free() */
+    aoc_string_free(out);
+}
+
+int main(void) {
+    size_t len;
+    char *day01 = read_file("../data/examples/day01.txt", &len);
+    check(2022, 1, 1, day01, len, AOC_STATUS_OK, "24000");
+    check(2022, 1, 2, day01, len, AOC_STATUS_OK, "45000");
+    free(day01);
+
+    const char *rounds = "A Y\nB X\nC Z";
+    check(2022, 2, 1, rounds, strlen(rounds), AOC_STATUS_OK, "15");
+    check(2022, 2, 1, "A Q", 3, AOC_STATUS_PARSE_ERROR, NULL);
+    check(2022, 2, 3, rounds, strlen(rounds), AOC_STATUS_UNKNOWN_PUZZLE, NULL);
+    check(2021, 2, 1, rounds, strlen(rounds), AOC_STATUS_UNKNOWN_PUZZLE, NULL);
+    check(2022, 2, 1, "\xff", 1, AOC_STATUS_BAD_ARGUMENT, NULL);
+
+    if (aoc_solve(2022, 2, 1, (const uint8_t *)rounds, strlen(rounds), NULL) != AOC_STATUS_BAD_ARGUMENT) {
+        printf("FAIL a null out pointer is not rejected\n");
+        failures++;
+    }
+
+    printf("%d failure(s)\n", failures);
+    return failures != 0;
+}
@@ -21,11 +21,10 @@
           buildInputs = [
             openssl
             pkg-config
-            (rust-bin.selectLatestNightlyWith (toolchain: toolchain.default.override {
+            (rust-bin.stable.latest.default.override {
               extensions = [ "rust-src" "rust-analyzer" "rustfmt" ];
-            }))
+            })
             cargo-nextest
-            # rust-bin.stable.latest.default
           ];
 
           # shellHook = ''
@@ -33,6 +32,18 @@
           #   alias find=fd
           # '';
         };
+
+        # for `cargo build --features nightly` and fuzzing
+        devShells.nightly = mkShell {
+          buildInputs = [
+            openssl
+            pkg-config
+            (rust-bin.selectLatestNightlyWith (toolchain: toolchain.default.override {
+              extensions = [ "rust-src" "rust-analyzer" "rustfmt" ];
+            }))
+            cargo-nextest
+          ];
+        };
       }
     );
 }
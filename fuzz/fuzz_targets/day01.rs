@@ -0,0 +1,14 @@
+#![no_main]
+
+use aoc2022::solutions::day01;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let data = String::from_utf8_lossy(data);
+    // the totals are only added up by the solvers, so they have to run too
+    if let Ok(elves) = day01::parse(&data) {
+        let _ = day01::solve_p1(&elves);
+        let _ = day01::solve_p2(&elves);
+    }
+    let _ = day01::solve_p2_stream(data.as_bytes());
+});
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = aoc2022::solutions::day02::parse(&String::from_utf8_lossy(data));
+});
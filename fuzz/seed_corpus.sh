@@ -0,0 +1,13 @@
+#!/usr/bin/env sh
+# Seeds the corpus of every fuzz target with the matching puzzle input from data/, so the fuzzer
+# starts out from inputs that make it all the way through the parsers, and with inputs that once
+# made a day panic.
+cd "$(dirname "$0")" || exit 1
+for input in ../data/day*.txt; do
+    target=$(basename "$input" .txt)
+    mkdir -p "corpus/$target"
+    cp "$input" "corpus/$target/"
+done
+printf '2000000000\n2000000000' > corpus/day01/overflowing_total.txt
+printf '2000000000\n\n2000000000' > corpus/day01/overflowing_top_three.txt
+printf '$ cd /\n$ ls\n18446744073709551615 a\n1 b' > corpus/day07/overflowing_sizes.txt
@@ -0,0 +1,74 @@
+/* Generated by cbindgen from src/common/capi.rs; do not edit by hand. */
+
+#ifndef AOC2022_H
+#define AOC2022_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Status codes of `aoc_solve`; the error codes mirror the variants of the solvers' error type.
+ */
+typedef enum AocStatus {
+  AOC_STATUS_OK = 0,
+  /**
+   * the input could not be parsed; the message names the line
+   */
+  AOC_STATUS_PARSE_ERROR = 1,
+  /**
+   * the input parsed, but has no answer
+   */
+  AOC_STATUS_INVALID_INPUT = 2,
+  /**
+   * the input could not be read
+   */
+  AOC_STATUS_IO_ERROR = 3,
+  /**
+   * there is no solver for that year, day or part
+   */
+  AOC_STATUS_UNKNOWN_PUZZLE = 4,
+  /**
+   * a null pointer or an input that is not UTF-8; nothing is written to `out`
+   */
+  AOC_STATUS_BAD_ARGUMENT = 5,
+  /**
+   * the solver panicked
+   */
+  AOC_STATUS_PANIC = 6,
+  /**
+   * the run went over its limits and was stopped
+   */
+  AOC_STATUS_CANCELLED = 7,
+} AocStatus;
+
+/**
+ * Solves `part` of `day` of `year` for the `len` bytes of UTF-8 input at `input`.
+ *
+ * On success `*out` is set to the answer, otherwise it is set to an error message, unless the
+ * status is `BadArgument`. Either way the string has to be released with `aoc_string_free`.
+ *
+ * # Safety
+ *
+ * `input` has to point to `len` readable bytes (it may be null if `len` is 0) and `out` has to
+ * point to writable memory for one pointer.
+ */
+enum AocStatus aoc_solve(uint32_t year,
+                         uint32_t day,
+                         uint32_t part,
+                         const uint8_t *input,
+                         size_t len,
+                         char **out);
+
+/**
+ * Releases a string handed out by `aoc_solve`; null is ignored.
+ *
+ * # Safety
+ *
+ * `s` has to be null or a string from `aoc_solve` that has not been released yet.
+ */
+void aoc_string_free(char *s);
+
+#endif  /* AOC2022_H */
@@ -1 +1,34 @@
+pub mod alloc;
+#[cfg(not(feature = "wasm"))]
+pub mod batch;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod cycle;
+pub mod error;
+pub mod geom;
+pub mod grid;
+pub mod image;
+pub mod interval;
+pub mod limits;
+pub mod ocr;
+pub mod params;
+pub mod parse;
+#[cfg(not(feature = "wasm"))]
 pub mod pick_challenge;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(not(feature = "wasm"))]
+pub mod report;
+pub mod runner;
+pub mod search;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod stream;
+pub mod topk;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(test)]
+pub mod differential;
@@ -0,0 +1,109 @@
+// Global allocator that keeps per-thread allocation statistics, so that a run on its own worker
+// thread can be measured and held to a memory limit. Only the binary installs it; without it all
+// the numbers simply stay at 0 and memory limits are not enforced.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+pub struct Counting;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// bytes currently allocated by this thread (and not yet freed by any thread)
+    pub live: isize,
+    /// highest `live` since the last `measure`
+    pub peak: isize,
+    /// number of allocations since the last `measure`
+    pub allocations: usize,
+    /// `live` when the measurement started
+    base: isize,
+    /// `live` may not go beyond this; 0 means there is no limit
+    limit: isize,
+    over_limit: bool,
+}
+
+thread_local! {
+    // const initialised and without a destructor, so it is safe to use from within the allocator
+    static STATS: Cell<Stats> = const {
+        Cell::new(Stats {
+            live: 0,
+            peak: 0,
+            allocations: 0,
+            base: 0,
+            limit: 0,
+            over_limit: false,
+        })
+    };
+}
+
+fn record(bytes: isize, allocations: usize) {
+    let _ = STATS.try_with(|stats| {
+        let mut s = stats.get();
+        s.live += bytes;
+        s.peak = s.peak.max(s.live);
+        s.allocations += allocations;
+        s.over_limit |= s.limit != 0 && s.live > s.limit;
+        stats.set(s);
+    });
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize, 1);
+        }
+        return ptr;
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize, 1);
+        }
+        return ptr;
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(-(layout.size() as isize), 0);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size as isize - layout.size() as isize, 1);
+        }
+        return new_ptr;
+    }
+}
+
+/// Starts a new measurement on this thread: resets the peak and the allocation count, and allows
+/// at most `limit` more bytes from here on.
+pub fn measure(limit: Option<usize>) {
+    STATS.with(|stats| {
+        let s = stats.get();
+        stats.set(Stats {
+            live: s.live,
+            peak: s.live,
+            allocations: 0,
+            base: s.live,
+            limit: limit.map_or(0, |limit| s.live + limit as isize),
+            over_limit: false,
+        });
+    });
+}
+
+/// Statistics of this thread since the last `measure`, with `live` and `peak` relative to the
+/// start of the measurement.
+pub fn stats() -> Stats {
+    let s = STATS.with(|stats| stats.get());
+    return Stats {
+        live: s.live - s.base,
+        peak: s.peak - s.base,
+        ..s
+    };
+}
+
+pub fn over_limit() -> bool {
+    return STATS.with(|stats| stats.get().over_limit);
+}
@@ -0,0 +1,159 @@
+use crate::common::limits::Limits;
+use crate::common::runner::{self, Run};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Outcome of running both parts of a day on one input file.
+pub struct Entry {
+    pub file: PathBuf,
+    pub result: Result<Run, String>,
+}
+
+/// Expands directories (to the files directly inside them) and glob patterns; plain paths are
+/// taken as they are. Everything is sorted, so the table comes out in the same order every time.
+pub fn collect_inputs(sources: &[String]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = vec![];
+    for source in sources {
+        let path = Path::new(source);
+        if path.is_dir() {
+            for entry in fs::read_dir(path)? {
+                let entry = entry?;
+                if entry.file_type()?.is_file() {
+                    files.push(entry.path());
+                }
+            }
+        } else if path.exists() {
+            files.push(path.to_path_buf());
+        } else {
+            let matches = glob::glob(source)?.collect::<Result<Vec<_>, _>>()?;
+            if matches.is_empty() {
+                return Err(format!(
+                    "{} is neither a file, nor a directory, nor matches any files",
+                    source
+                )
+                .into());
+            }
+            files.extend(matches.into_iter().filter(|path| path.is_file()));
+        }
+    }
+    files.sort();
+    files.dedup();
+    return Ok(files);
+}
+
+/// Runs both parts of `day` on every file, each under `limits`; an input that goes over them is
+/// reported as failed, like any other error.
+pub fn run_batch(day: i32, files: &[PathBuf], limits: &Limits) -> Vec<Entry> {
+    return files
+        .iter()
+        .map(|file| Entry {
+            file: file.clone(),
+            result: fs::read_to_string(file)
+                .map_err(|e| format!("could not read the file: {}", e))
+                .and_then(|data| {
+                    runner::run_limited(day, &[1, 2], &data, limits).map_err(|e| e.to_string())
+                }),
+        })
+        .collect();
+}
+
+fn micros(duration: Duration) -> String {
+    return format!("{}µs", duration.as_micros());
+}
+
+/// Lays out the entries as a table with one row per file; pictures (day10's CRT) do not fit into a
+/// cell, so they are printed below the table and referenced from it.
+pub fn render_table(entries: &[Entry]) -> String {
+    let header = [
+        "file",
+        "part 1",
+        "part 2",
+        "parse",
+        "part 1 time",
+        "part 2 time",
+    ]
+    .map(String::from);
+    let mut rows = vec![header.to_vec()];
+    let mut pictures = vec![];
+    for entry in entries {
+        let mut row = vec![entry.file.display().to_string()];
+        match &entry.result {
+            Ok(run) => {
+                for part in run.parts.iter() {
+                    let Some(picture) = &part.picture else {
+                        row.push(part.answer.clone());
+                        continue;
+                    };
+                    pictures.push((entry.file.display().to_string(), part.part, picture.clone()));
+                    // undecoded, the answer is the picture itself
+                    row.push(match part.answer.contains('\n') {
+                        true => format!("[picture {}]", pictures.len()),
+                        false => format!("{} [picture {}]", part.answer, pictures.len()),
+                    });
+                }
+                row.push(micros(run.parse_time));
+                row.extend(run.parts.iter().map(|part| micros(part.solve_time)));
+            }
+            Err(e) => row.push(format!("FAILED: {}", e)),
+        }
+        rows.push(row);
+    }
+
+    let mut widths = vec![0; header.len()];
+    for row in rows.iter() {
+        // the error message of a failed file spans the rest of the row, so it does not count
+        if row.len() == header.len() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+    }
+    let mut table = String::new();
+    for row in rows.iter() {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    for (i, (file, part, picture)) in pictures.iter().enumerate() {
+        table.push_str(&format!(
+            "\n[picture {}] {}, part {}:\n{}\n",
+            i + 1,
+            file,
+            part,
+            picture.trim_end()
+        ));
+    }
+    return table;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render_table, Entry};
+    use crate::common::runner;
+    use std::path::PathBuf;
+
+    #[test]
+    fn table() {
+        let entries = vec![
+            Entry {
+                file: PathBuf::from("alice.txt"),
+                result: Ok(runner::run(2, &[1, 2], "A Y\nB X\nC Z").unwrap()),
+            },
+            Entry {
+                file: PathBuf::from("bob.txt"),
+                result: Err("parse error in line 1: broken".to_string()),
+            },
+        ];
+        let table = render_table(&entries);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[0].starts_with("file       part 1  part 2  parse"));
+        assert!(lines[1].starts_with("alice.txt  15      12      "));
+        assert_eq!(lines[2], "bob.txt    FAILED: parse error in line 1: broken");
+    }
+}
@@ -0,0 +1,140 @@
+// C ABI for linking the solvers into other languages; the declarations live in include/aoc2022.h,
+// which `make -C capi header` regenerates, and which builds with the capi feature warn about once
+// it is out of date.
+//
+// Ownership: the input is only borrowed for the duration of the call. Every string handed out
+// through `out` is owned by the caller and has to be released with `aoc_string_free`, never with
+// free().
+use crate::common::error::SolveError;
+use crate::common::runner::{self, RunError};
+use std::ffi::{c_char, CString};
+use std::panic;
+use std::slice;
+
+/// Status codes of `aoc_solve`; the error codes mirror the variants of the solvers' error type.
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum AocStatus {
+    Ok = 0,
+    /// the input could not be parsed; the message names the line
+    ParseError = 1,
+    /// the input parsed, but has no answer
+    InvalidInput = 2,
+    /// the input could not be read
+    IoError = 3,
+    /// there is no solver for that year, day or part
+    UnknownPuzzle = 4,
+    /// a null pointer or an input that is not UTF-8; nothing is written to `out`
+    BadArgument = 5,
+    /// the solver panicked
+    Panic = 6,
+    /// the run went over its limits and was stopped
+    Cancelled = 7,
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    // error messages quote parts of the input, which could contain NUL bytes
+    let s = CString::new(s.replace('\0', "\\0")).unwrap();
+    return s.into_raw();
+}
+
+/// Solves `part` of `day` of `year` for the `len` bytes of UTF-8 input at `input`.
+///
+/// On success `*out` is set to the answer, otherwise it is set to an error message, unless the
+/// status is `BadArgument`. Either way the string has to be released with `aoc_string_free`.
+///
+/// # Safety
+///
+/// `input` has to point to `len` readable bytes (it may be null if `len` is 0) and `out` has to
+/// point to writable memory for one pointer.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(
+    year: u32,
+    day: u32,
+    part: u32,
+    input: *const u8,
+    len: usize,
+    out: *mut *mut c_char,
+) -> AocStatus {
+    if out.is_null() || (input.is_null() && len > 0) {
+        return AocStatus::BadArgument;
+    }
+    let bytes = match input.is_null() {
+        true => &[][..],
+        false => slice::from_raw_parts(input, len),
+    };
+    let Ok(data) = std::str::from_utf8(bytes) else {
+        return AocStatus::BadArgument;
+    };
+    if year != 2022 {
+        *out = into_c_string(format!(
+            "only the puzzles of 2022 are solved here, not {}",
+            year
+        ));
+        return AocStatus::UnknownPuzzle;
+    }
+
+    let run = panic::catch_unwind(|| runner::run(day as i32, &[part as i32], data));
+    let (status, message) = match run {
+        Ok(Ok(mut run)) => (AocStatus::Ok, run.parts.remove(0).answer),
+        Ok(Err(e)) => {
+            let status = match &e {
+                RunError::UnknownDay(_) | RunError::UnknownPart(_) => AocStatus::UnknownPuzzle,
+                RunError::Solve(SolveError::Parse { .. }) => AocStatus::ParseError,
+                RunError::Solve(SolveError::Invalid(_)) => AocStatus::InvalidInput,
+                RunError::Solve(SolveError::Io(_)) => AocStatus::IoError,
+                RunError::Solve(SolveError::Cancelled(_)) => AocStatus::Cancelled,
+            };
+            (status, e.to_string())
+        }
+        Err(_) => (AocStatus::Panic, "the solver panicked".to_string()),
+    };
+    *out = into_c_string(message);
+    return status;
+}
+
+/// Releases a string handed out by `aoc_solve`; null is ignored.
+///
+/// # Safety
+///
+/// `s` has to be null or a string from `aoc_solve` that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{aoc_solve, aoc_string_free, AocStatus};
+    use std::ffi::CStr;
+    use std::ptr;
+
+    fn solve(year: u32, day: u32, part: u32, input: &str) -> (AocStatus, String) {
+        let mut out = ptr::null_mut();
+        unsafe {
+            let status = aoc_solve(year, day, part, input.as_ptr(), input.len(), &mut out);
+            let message = CStr::from_ptr(out).to_str().unwrap().to_string();
+            aoc_string_free(out);
+            return (status, message);
+        }
+    }
+
+    #[test]
+    fn statuses() {
+        assert_eq!(
+            solve(2022, 2, 1, "A Y\nB X\nC Z"),
+            (AocStatus::Ok, "15".to_string())
+        );
+        assert_eq!(solve(2022, 2, 1, "A Q").0, AocStatus::ParseError);
+        assert_eq!(
+            solve(2022, 7, 2, "$ cd /\n$ ls\n70000001 a").0,
+            AocStatus::InvalidInput
+        );
+        assert_eq!(solve(2021, 2, 1, "").0, AocStatus::UnknownPuzzle);
+        assert_eq!(solve(2022, 2, 3, "").0, AocStatus::UnknownPuzzle);
+        let status = unsafe { aoc_solve(2022, 1, 1, ptr::null(), 0, ptr::null_mut()) };
+        assert_eq!(status, AocStatus::BadArgument);
+    }
+}
@@ -0,0 +1,260 @@
+// Cycle detection for simulations that go on for far more steps than can be simulated one by one.
+// A simulation is given by its first state and a step function, and its states are compared by
+// their fingerprints, which can leave out anything that does not influence the steps to come,
+// like counters of what happened so far. Every search gives up after `limit` steps, since not
+// every simulation repeats itself.
+use crate::common::error::SolveError;
+use crate::common::limits;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The states from step `start` on repeat every `length` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+/// Floyd's tortoise and hare, which only keeps two states around at a time.
+pub fn floyd<S, K: PartialEq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    fingerprint: impl Fn(&S) -> K,
+    limit: usize,
+) -> Result<Option<Cycle>, SolveError> {
+    // the hare runs twice as fast, so both meet within the cycle, at a multiple of its length
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    let mut steps = 1;
+    while fingerprint(&tortoise) != fingerprint(&hare) {
+        limits::check()?;
+        if steps >= limit {
+            return Ok(None);
+        }
+        tortoise = step(&tortoise);
+        let halfway = step(&hare);
+        hare = step(&halfway);
+        steps += 1;
+    }
+    // which means that the start of the cycle is as far from the meeting point as from the
+    // initial state
+    let mut start = 0;
+    let mut tortoise = initial;
+    while fingerprint(&tortoise) != fingerprint(&hare) {
+        limits::check()?;
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    let mut length = 1;
+    let mut hare = step(&tortoise);
+    while fingerprint(&tortoise) != fingerprint(&hare) {
+        limits::check()?;
+        hare = step(&hare);
+        length += 1;
+    }
+    return Ok(Some(Cycle { start, length }));
+}
+
+/// Brent's algorithm, which keeps two states around like Floyd's, but takes fewer steps.
+pub fn brent<S: Clone, K: PartialEq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    fingerprint: impl Fn(&S) -> K,
+    limit: usize,
+) -> Result<Option<Cycle>, SolveError> {
+    // the tortoise waits for the hare at every power of two, until the hare comes around to it
+    let (mut power, mut length) = (1, 1);
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    let mut steps = 1;
+    while fingerprint(&tortoise) != fingerprint(&hare) {
+        limits::check()?;
+        if steps >= limit {
+            return Ok(None);
+        }
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+        steps += 1;
+    }
+    // with the hare a cycle ahead, both meet at its start
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while fingerprint(&tortoise) != fingerprint(&hare) {
+        limits::check()?;
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    return Ok(Some(Cycle { start, length }));
+}
+
+/// Remembers the fingerprint of every state, and so finds the cycle as soon as a state repeats.
+pub fn find<S, K: Eq + Hash>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    fingerprint: impl Fn(&S) -> K,
+    limit: usize,
+) -> Result<Option<Cycle>, SolveError> {
+    let mut seen = HashMap::new();
+    let mut state = initial;
+    for i in 0..=limit {
+        limits::check()?;
+        if let Some(start) = seen.insert(fingerprint(&state), i) {
+            return Ok(Some(Cycle {
+                start,
+                length: i - start,
+            }));
+        }
+        state = step(&state);
+    }
+    return Ok(None);
+}
+
+/// Something that adds up over the steps of a simulation, like how often something happened.
+pub trait Metric: Default + Clone {
+    /// Adds `other` to this, `times` times over.
+    fn add(&mut self, other: &Self, times: u64);
+}
+
+impl Metric for u64 {
+    fn add(&mut self, other: &Self, times: u64) {
+        *self += other * times;
+    }
+}
+
+impl Metric for usize {
+    fn add(&mut self, other: &Self, times: u64) {
+        *self += other * times as usize;
+    }
+}
+
+/// Adds up element by element, e.g. a count for each of several things.
+impl<T: Metric> Metric for Vec<T> {
+    fn add(&mut self, other: &Self, times: u64) {
+        if self.len() < other.len() {
+            self.resize(other.len(), T::default());
+        }
+        for (total, value) in self.iter_mut().zip(other.iter()) {
+            total.add(value, times);
+        }
+    }
+}
+
+/// The state after `steps` steps from `initial`, where every step also yields a metric, and the
+/// sum of those metrics. As soon as a state repeats, the rest of the steps are skipped, adding up
+/// the metrics of the cycle as often as it would have been run. The first step that fails ends
+/// the simulation with its error.
+pub fn fast_forward<S: Clone, K: Eq + Hash, M: Metric>(
+    initial: S,
+    steps: usize,
+    mut step: impl FnMut(&S) -> Result<(S, M), SolveError>,
+    fingerprint: impl Fn(&S) -> K,
+) -> Result<(S, M), SolveError> {
+    let mut seen = HashMap::new();
+    // the state after every step so far, and the metric of every step
+    let mut states = vec![initial];
+    let mut metrics: Vec<M> = vec![];
+    let mut total = M::default();
+    for i in 0..steps {
+        limits::check()?;
+        if let Some(start) = seen.insert(fingerprint(&states[i]), i) {
+            let (length, remaining) = (i - start, steps - i);
+            let mut cycle = M::default();
+            for metric in metrics[start..i].iter() {
+                cycle.add(metric, 1);
+            }
+            total.add(&cycle, (remaining / length) as u64);
+            let rest = remaining % length;
+            for metric in metrics[start..start + rest].iter() {
+                total.add(metric, 1);
+            }
+            return Ok((states.swap_remove(start + rest), total));
+        }
+        let (state, metric) = step(&states[i])?;
+        total.add(&metric, 1);
+        states.push(state);
+        metrics.push(metric);
+    }
+    return Ok((states.pop().unwrap(), total));
+}
+
+#[cfg(test)]
+mod test {
+    use super::{brent, fast_forward, find, floyd, Cycle, Metric};
+    use crate::common::error::SolveError;
+
+    // 3 has order 30 modulo 77, so the powers of 3 repeat from the very first one
+    fn step(n: &u64) -> u64 {
+        return n * 3 % 77;
+    }
+
+    #[test]
+    fn detection() {
+        let expected = find(3, step, |n| *n, 100).unwrap().unwrap();
+        assert_eq!(expected.length, 30);
+        assert_eq!(expected.start, 0);
+        assert_eq!(floyd(3, step, |n| *n, 100), Ok(Some(expected)));
+        assert_eq!(brent(3, step, |n| *n, 100), Ok(Some(expected)));
+
+        // a tail of five steps, then 5, 6, 7, 8 over and over
+        let walk = |n: &i32| if *n < 5 { n + 1 } else { 5 + (n - 5 + 1) % 4 };
+        let expected = Some(Cycle {
+            start: 5,
+            length: 4,
+        });
+        assert_eq!(find(0, walk, |n| *n, 100), Ok(expected));
+        assert_eq!(floyd(0, walk, |n| *n, 100), Ok(expected));
+        assert_eq!(brent(0, walk, |n| *n, 100), Ok(expected));
+        assert_eq!(find(0, |n| n + 1, |n| *n, 100), Ok(None));
+        assert_eq!(floyd(0, |n| n + 1, |n| *n, 100), Ok(None));
+        assert_eq!(brent(0, |n| n + 1, |n| *n, 100), Ok(None));
+    }
+
+    #[test]
+    fn fast_forwarding() {
+        // counts how often each remainder modulo 4 has been stepped away from
+        let walk = |n: &i32| {
+            let mut visits = vec![0u64; 4];
+            visits[(*n % 4) as usize] = 1;
+            return Ok((if *n < 5 { n + 1 } else { 5 + (n - 5 + 1) % 4 }, visits));
+        };
+        let slow = |steps: usize| {
+            let (mut state, mut visits) = (0, vec![]);
+            for _ in 0..steps {
+                let (next, step_visits) = walk(&state).unwrap();
+                state = next;
+                visits.add(&step_visits, 1);
+            }
+            return (state, visits);
+        };
+        for steps in [0, 3, 5, 9, 10, 1_000, 1_001, 1_002, 1_003] {
+            assert_eq!(fast_forward(0, steps, walk, |n| *n), Ok(slow(steps)));
+        }
+        let (_, visits) = fast_forward(0, 1_000_000_000_000, walk, |n| *n).unwrap();
+        assert_eq!(visits.iter().sum::<u64>(), 1_000_000_000_000);
+
+        // a failing step ends it all
+        let failing = |n: &u64| {
+            if *n < 3 {
+                Ok((n + 1, 1u64))
+            } else {
+                Err(SolveError::invalid("3"))
+            }
+        };
+        assert_eq!(fast_forward(0, 3, failing, |n| *n), Ok((3, 3)));
+        assert_eq!(
+            fast_forward(0, 4, failing, |n| *n),
+            Err(SolveError::invalid("3"))
+        );
+    }
+}
@@ -0,0 +1,68 @@
+// Small harness for differential testing: every day keeps a naive, obviously-correct reference
+// implementation next to its tests, and this runs both the real solver and the reference over a
+// bunch of generated inputs, panicking with the first input they disagree on.
+use std::fmt::Debug;
+
+// fixed seed, so that a failing case can be reproduced by just rerunning the test
+const SEED: u64 = 0x2022_1225;
+
+// SplitMix64; more than good enough to generate puzzle inputs and saves us a dependency
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        return Rng(seed);
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return z ^ (z >> 31);
+    }
+
+    /// random number in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        return (self.next_u64() % n as u64) as usize;
+    }
+
+    /// random number in `lo..=hi`
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        return lo + (self.next_u64() % (hi - lo + 1) as u64) as i64;
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        return &items[self.below(items.len())];
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Generates `cases` inputs and checks that `solver` and `reference` agree on each of them.
+pub fn check<T, G, S, R>(cases: usize, mut generate: G, solver: S, reference: R)
+where
+    T: PartialEq + Debug,
+    G: FnMut(&mut Rng) -> String,
+    S: Fn(&str) -> T,
+    R: Fn(&str) -> T,
+{
+    let mut rng = Rng::new(SEED);
+    for case in 0..cases {
+        let input = generate(&mut rng);
+        let expected = reference(&input);
+        let actual = solver(&input);
+        assert!(
+            actual == expected,
+            "case {}: solver returned {:?}, but the reference returned {:?} for input:\n{}",
+            case,
+            actual,
+            expected,
+            input
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use crate::common::limits::Limit;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// the input does not look like what the puzzle describes; `line` is 1-based
+    Parse { line: usize, msg: String },
+    /// the input parsed fine, but there is no answer for it
+    Invalid(String),
+    /// reading a streamed input failed
+    Io(String),
+    /// the run was stopped for going over one of its limits
+    Cancelled(Limit),
+}
+
+impl SolveError {
+    pub fn parse(line: usize, msg: impl Into<String>) -> Self {
+        return SolveError::Parse {
+            line,
+            msg: msg.into(),
+        };
+    }
+
+    pub fn invalid(msg: impl Into<String>) -> Self {
+        return SolveError::Invalid(msg.into());
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Parse { line, msg } => write!(f, "parse error in line {}: {}", line, msg),
+            SolveError::Invalid(msg) => write!(f, "invalid input: {}", msg),
+            SolveError::Io(msg) => write!(f, "could not read the input: {}", msg),
+            SolveError::Cancelled(Limit::Time) => write!(f, "timed out"),
+            SolveError::Cancelled(Limit::Memory) => write!(f, "went over the memory limit"),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+impl From<io::Error> for SolveError {
+    fn from(e: io::Error) -> Self {
+        return SolveError::Io(e.to_string());
+    }
+}
@@ -0,0 +1,238 @@
+// Points and vectors on the integer plane. Like in grid.rs, x grows to the right and y grows
+// downwards, so going up decreases y, and the grid position of a point is (y, x).
+use crate::common::grid::{Grid, Pos};
+use std::num::TryFromIntError;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// The difference between two points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Vec2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        return Point { x, y };
+    }
+
+    pub fn manhattan(self, other: Point) -> u32 {
+        return (other - self).manhattan_len();
+    }
+
+    pub fn chebyshev(self, other: Point) -> u32 {
+        return (other - self).chebyshev_len();
+    }
+
+    /// One step towards `target`, diagonally if need be; stays put on the target itself.
+    pub fn step_towards(self, target: Point) -> Point {
+        return self + (target - self).signum();
+    }
+
+    /// The grid position of this point, if it is not left of or above the grid.
+    pub fn to_pos(self) -> Option<Pos> {
+        return Some((usize::try_from(self.y).ok()?, usize::try_from(self.x).ok()?));
+    }
+}
+
+impl TryFrom<Pos> for Point {
+    type Error = TryFromIntError;
+
+    fn try_from((row, col): Pos) -> Result<Self, Self::Error> {
+        return Ok(Point::new(i32::try_from(col)?, i32::try_from(row)?));
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        let pos = point
+            .to_pos()
+            .unwrap_or_else(|| panic!("{:?} is off the grid", point));
+        return &self[pos];
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        let pos = point
+            .to_pos()
+            .unwrap_or_else(|| panic!("{:?} is off the grid", point));
+        return &mut self[pos];
+    }
+}
+
+impl Vec2 {
+    pub const fn new(x: i32, y: i32) -> Self {
+        return Vec2 { x, y };
+    }
+
+    /// The vector with both components reduced to -1, 0 or 1.
+    pub fn signum(self) -> Vec2 {
+        return Vec2::new(self.x.signum(), self.y.signum());
+    }
+
+    /// The vector with both components clamped to `-limit..=limit`.
+    pub fn clamp(self, limit: i32) -> Vec2 {
+        return Vec2::new(self.x.clamp(-limit, limit), self.y.clamp(-limit, limit));
+    }
+
+    pub fn manhattan_len(self) -> u32 {
+        return self.x.unsigned_abs() + self.y.unsigned_abs();
+    }
+
+    pub fn chebyshev_len(self) -> u32 {
+        return self.x.unsigned_abs().max(self.y.unsigned_abs());
+    }
+}
+
+impl Add<Vec2> for Point {
+    type Output = Point;
+
+    fn add(self, v: Vec2) -> Point {
+        return Point::new(self.x + v.x, self.y + v.y);
+    }
+}
+
+impl AddAssign<Vec2> for Point {
+    fn add_assign(&mut self, v: Vec2) {
+        *self = *self + v;
+    }
+}
+
+impl Sub<Vec2> for Point {
+    type Output = Point;
+
+    fn sub(self, v: Vec2) -> Point {
+        return Point::new(self.x - v.x, self.y - v.y);
+    }
+}
+
+impl SubAssign<Vec2> for Point {
+    fn sub_assign(&mut self, v: Vec2) {
+        *self = *self - v;
+    }
+}
+
+impl Sub for Point {
+    type Output = Vec2;
+
+    fn sub(self, other: Point) -> Vec2 {
+        return Vec2::new(self.x - other.x, self.y - other.y);
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        return Vec2::new(self.x + other.x, self.y + other.y);
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        return Vec2::new(self.x - other.x, self.y - other.y);
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        return Vec2::new(-self.x, -self.y);
+    }
+}
+
+impl Mul<i32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, factor: i32) -> Vec2 {
+        return Vec2::new(self.x * factor, self.y * factor);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Left,
+        Direction::Down,
+        Direction::Right,
+    ];
+
+    pub fn unit(self) -> Vec2 {
+        return match self {
+            Direction::Up => Vec2::new(0, -1),
+            Direction::Down => Vec2::new(0, 1),
+            Direction::Left => Vec2::new(-1, 0),
+            Direction::Right => Vec2::new(1, 0),
+        };
+    }
+}
+
+/// Parses the initials `U`, `D`, `L` and `R`.
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s {
+            "U" => Ok(Direction::Up),
+            "D" => Ok(Direction::Down),
+            "L" => Ok(Direction::Left),
+            "R" => Ok(Direction::Right),
+            _ => Err(format!("expected one of U, D, L and R, got {:?}", s)),
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Direction, Point, Vec2};
+
+    #[test]
+    fn arithmetic_and_distances() {
+        let (a, b) = (Point::new(1, 2), Point::new(4, -2));
+        assert_eq!(b - a, Vec2::new(3, -4));
+        assert_eq!(a + (b - a), b);
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+        assert_eq!(a.step_towards(b), Point::new(2, 1));
+        assert_eq!(b.step_towards(b), b);
+        assert_eq!(Vec2::new(5, -1).clamp(2), Vec2::new(2, -1));
+        assert_eq!(-Vec2::new(1, -1) * 3, Vec2::new(-3, 3));
+    }
+
+    #[test]
+    fn directions_and_grid_positions() {
+        let moves: Vec<Direction> = "R U L D".split(' ').map(|s| s.parse().unwrap()).collect();
+        let end = moves.iter().fold(Point::ORIGIN, |p, d| p + d.unit());
+        assert_eq!(end, Point::ORIGIN);
+        assert!("X".parse::<Direction>().is_err());
+        assert_eq!(
+            (Point::ORIGIN + Direction::Down.unit()).to_pos(),
+            Some((1, 0))
+        );
+        assert_eq!((Point::ORIGIN + Direction::Up.unit()).to_pos(), None);
+        assert_eq!(Point::try_from((3, 5)), Ok(Point::new(5, 3)));
+    }
+}
@@ -0,0 +1,300 @@
+// Rectangular grids, like the forest of day08 and the heightmap of day12. Positions are
+// (row, column) pairs, starting at (0, 0) in the top left corner, and steps between neighboring
+// cells go in one of the directions of geom.rs.
+use crate::common::error::SolveError;
+use crate::common::geom::{Direction, Vec2};
+use std::fmt;
+use std::iter;
+use std::ops::{Index, IndexMut};
+
+pub type Pos = (usize, usize);
+
+// the steps to all eight neighbors, row after row
+const NEIGHBORS_8: [Vec2; 8] = [
+    Vec2::new(-1, -1),
+    Vec2::new(0, -1),
+    Vec2::new(1, -1),
+    Vec2::new(-1, 0),
+    Vec2::new(1, 0),
+    Vec2::new(-1, 1),
+    Vec2::new(0, 1),
+    Vec2::new(1, 1),
+];
+
+/// A grid of `height` rows of `width` cells each, stored row after row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        return Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        };
+    }
+
+    /// Parses a map with one character per cell, like
+    ///
+    /// ```text
+    /// 303
+    /// 255
+    /// ```
+    ///
+    /// The errors of `cell` are reported for the line they were found in; empty maps and lines of
+    /// different lengths are rejected.
+    pub fn parse(
+        data: &str,
+        mut cell: impl FnMut(char) -> Result<T, String>,
+    ) -> Result<Self, SolveError> {
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+        for (i, line) in data.lines().enumerate() {
+            let length = cells.len();
+            for c in line.chars() {
+                cells.push(cell(c).map_err(|msg| SolveError::parse(i + 1, msg))?);
+            }
+            let line_width = cells.len() - length;
+            match width {
+                None => width = Some(line_width),
+                Some(width) if width != line_width => {
+                    return Err(SolveError::parse(
+                        i + 1,
+                        format!(
+                            "expected {} cells like in the first line, got {}",
+                            width, line_width
+                        ),
+                    ));
+                }
+                Some(_) => {}
+            }
+            height += 1;
+        }
+        let width = width.unwrap_or(0);
+        if width == 0 {
+            return Err(SolveError::parse(1, "the map is empty"));
+        }
+        return Ok(Grid {
+            width,
+            height,
+            cells,
+        });
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn get(&self, (row, col): Pos) -> Option<&T> {
+        if row >= self.height || col >= self.width {
+            return None;
+        }
+        return self.cells.get(row * self.width + col);
+    }
+
+    pub fn get_mut(&mut self, (row, col): Pos) -> Option<&mut T> {
+        if row >= self.height || col >= self.width {
+            return None;
+        }
+        return self.cells.get_mut(row * self.width + col);
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        return &self.cells[row * self.width..(row + 1) * self.width];
+    }
+
+    pub fn column(&self, col: usize) -> impl DoubleEndedIterator<Item = &T> {
+        return (0..self.height).map(move |row| &self[(row, col)]);
+    }
+
+    /// Every row, even the empty ones of a grid without columns.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        return (0..self.height).map(move |row| self.row(row));
+    }
+
+    /// All cells with their positions, row after row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        let width = self.width;
+        return self
+            .cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i / width, i % width), cell));
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let (width, height) = (self.width, self.height);
+        return (0..height).flat_map(move |row| (0..width).map(move |col| (row, col)));
+    }
+
+    /// Position of the first cell, row after row, that `predicate` holds for.
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Pos> {
+        return self
+            .cells
+            .iter()
+            .position(predicate)
+            .map(|i| (i / self.width, i % self.width));
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        return Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        };
+    }
+
+    /// The neighboring position in `direction`, if it is still on the grid.
+    pub fn step(&self, pos: Pos, direction: Direction) -> Option<Pos> {
+        return step(self.width, self.height, pos, direction.unit());
+    }
+
+    // the iterators below only hold on to the size of the grid, so the cells can be changed while
+    // walking over their positions
+
+    pub fn neighbors_4(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        let (width, height) = (self.width, self.height);
+        return Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| step(width, height, pos, direction.unit()));
+    }
+
+    pub fn neighbors_8(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        let (width, height) = (self.width, self.height);
+        return NEIGHBORS_8
+            .into_iter()
+            .filter_map(move |offset| step(width, height, pos, offset));
+    }
+
+    /// The positions from `pos` to the edge of the grid in `direction`, nearest first and without
+    /// `pos` itself.
+    pub fn ray(&self, pos: Pos, direction: Direction) -> impl Iterator<Item = Pos> {
+        let (width, height, offset) = (self.width, self.height, direction.unit());
+        return iter::successors(step(width, height, pos, offset), move |pos| {
+            step(width, height, *pos, offset)
+        });
+    }
+}
+
+fn step(width: usize, height: usize, (row, col): Pos, offset: Vec2) -> Option<Pos> {
+    let row = row
+        .checked_add_signed(offset.y as isize)
+        .filter(|row| *row < height)?;
+    let col = col
+        .checked_add_signed(offset.x as isize)
+        .filter(|col| *col < width)?;
+    return Some((row, col));
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): Pos) -> &T {
+        assert!(col < self.width, "column {} is off the grid", col);
+        return &self.cells[row * self.width + col];
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, (row, col): Pos) -> &mut T {
+        assert!(col < self.width, "column {} is off the grid", col);
+        return &mut self.cells[row * self.width + col];
+    }
+}
+
+/// Prints the cells row after row, without anything between them; this is the inverse of `parse`
+/// for cells that print as a single character.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Grid;
+    use crate::common::error::SolveError;
+    use crate::common::geom::Direction;
+
+    fn digits(data: &str) -> Result<Grid<u32>, SolveError> {
+        return Grid::parse(data, |c| {
+            c.to_digit(10)
+                .ok_or_else(|| format!("{:?} is not a digit", c))
+        });
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![3, 6]);
+        assert_eq!(grid.position(|cell| *cell == 5), Some((1, 1)));
+        assert_eq!(grid.to_string(), "123\n456");
+        // without columns, the rows are still there, just empty
+        let empty = Grid::new(0, 2, 0);
+        assert_eq!(
+            empty.rows().map(|row| row.len()).collect::<Vec<_>>(),
+            vec![0, 0]
+        );
+
+        assert_eq!(
+            digits("12\n3x").unwrap_err(),
+            SolveError::parse(2, "'x' is not a digit")
+        );
+        assert_eq!(
+            digits("12\n3").unwrap_err(),
+            SolveError::parse(2, "expected 2 cells like in the first line, got 1")
+        );
+        assert_eq!(
+            digits("").unwrap_err(),
+            SolveError::parse(1, "the map is empty")
+        );
+    }
+
+    #[test]
+    fn neighbors_and_rays() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(
+            grid.neighbors_4((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbors_4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbors_8((1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbors_8((2, 2)).collect::<Vec<_>>(),
+            vec![(1, 1), (1, 2), (2, 1)]
+        );
+        assert_eq!(
+            grid.ray((0, 0), Direction::Right).collect::<Vec<_>>(),
+            vec![(0, 1), (0, 2)]
+        );
+        assert_eq!(
+            grid.ray((2, 1), Direction::Up).collect::<Vec<_>>(),
+            vec![(1, 1), (0, 1)]
+        );
+        assert_eq!(grid.ray((2, 1), Direction::Down).count(), 0);
+    }
+}
@@ -0,0 +1,174 @@
+// Pictures of grid-shaped puzzle states, like the visibility map of day08 or the screen of day10,
+// written in the binary Netpbm formats: PBM for black and white, PGM for shades of gray and PPM for
+// colors. Every one of them is a short text header followed by the raw pixels, which most image
+// viewers open without further ado.
+use crate::common::grid::{Grid, Pos};
+use std::path::Path;
+
+/// Red, green and blue, from 0 to 255 each.
+pub type Color = [u8; 3];
+
+pub const BLACK: Color = [0, 0, 0];
+pub const WHITE: Color = [255, 255, 255];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Pbm,
+    Pgm,
+    Ppm,
+}
+
+impl Format {
+    /// The format going with the extension of `path`, i.e. .pbm, .pgm or .ppm.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        return match extension.as_str() {
+            "pbm" => Some(Format::Pbm),
+            "pgm" => Some(Format::Pgm),
+            "ppm" => Some(Format::Ppm),
+            _ => None,
+        };
+    }
+}
+
+/// The color `value` stands for on a scale from 0 to `max`, going from `low` to `high`.
+pub fn shade(low: Color, high: Color, value: usize, max: usize) -> Color {
+    let value = value.min(max);
+    let mut color = low;
+    for (channel, (low, high)) in color.iter_mut().zip(low.iter().zip(high.iter())) {
+        let (low, high) = (*low as usize, *high as usize);
+        *channel = match max {
+            0 => high as u8,
+            _ => ((low * (max - value) + high * value) / max) as u8,
+        };
+    }
+    return color;
+}
+
+// the brightness of a color, as the weighted sum of its channels that gray scale TV used
+fn luma([r, g, b]: Color) -> u8 {
+    return ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8;
+}
+
+/// A picture of `height` rows of `width` pixels each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Grid<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Color) -> Self {
+        return Image {
+            pixels: Grid::new(width, height, background),
+        };
+    }
+
+    /// One pixel per cell of `grid`, colored by `palette`.
+    pub fn from_grid<T>(grid: &Grid<T>, palette: impl FnMut(&T) -> Color) -> Self {
+        return Image {
+            pixels: grid.map(palette),
+        };
+    }
+
+    pub fn width(&self) -> usize {
+        return self.pixels.width();
+    }
+
+    pub fn height(&self) -> usize {
+        return self.pixels.height();
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<Color> {
+        return self.pixels.get(pos).copied();
+    }
+
+    /// Colors the pixel at `pos`; pixels outside of the picture are left alone.
+    pub fn set(&mut self, pos: Pos, color: Color) {
+        if let Some(pixel) = self.pixels.get_mut(pos) {
+            *pixel = color;
+        }
+    }
+
+    /// Every pixel blown up to a square of `factor` by `factor` pixels, since a tree or a screen
+    /// pixel per image pixel is hard to make out.
+    pub fn scaled(&self, factor: usize) -> Image {
+        let mut scaled = Image::new(self.width() * factor, self.height() * factor, BLACK);
+        for ((row, col), color) in self.pixels.iter() {
+            for pos in
+                (0..factor * factor).map(|k| (row * factor + k / factor, col * factor + k % factor))
+            {
+                scaled.set(pos, *color);
+            }
+        }
+        return scaled;
+    }
+
+    /// The picture as a file in `format`. PBM only knows black and white, so pixels darker than
+    /// middle gray turn black and the others white; PGM keeps their brightness.
+    pub fn encode(&self, format: Format) -> Vec<u8> {
+        let (width, height) = (self.width(), self.height());
+        let mut data = match format {
+            Format::Pbm => format!("P4\n{} {}\n", width, height),
+            Format::Pgm => format!("P5\n{} {}\n255\n", width, height),
+            Format::Ppm => format!("P6\n{} {}\n255\n", width, height),
+        }
+        .into_bytes();
+        for row in self.pixels.rows() {
+            match format {
+                // eight pixels to a byte, first one in the highest bit, with 1 for black; every
+                // row starts with a new byte
+                Format::Pbm => {
+                    for pixels in row.chunks(8) {
+                        let byte = pixels
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, color)| luma(**color) < 128)
+                            .fold(0u8, |byte, (i, _)| byte | 0x80 >> i);
+                        data.push(byte);
+                    }
+                }
+                Format::Pgm => data.extend(row.iter().map(|color| luma(*color))),
+                Format::Ppm => data.extend(row.iter().flatten()),
+            }
+        }
+        return data;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{shade, Format, Image, BLACK, WHITE};
+    use crate::common::grid::Grid;
+    use std::path::Path;
+
+    #[test]
+    fn encoding() {
+        let grid = Grid::parse("#..\n.#.", |c| Ok(c == '#')).unwrap();
+        let image = Image::from_grid(&grid, |lit| if *lit { WHITE } else { BLACK });
+        assert_eq!(image.encode(Format::Pbm), b"P4\n3 2\n\x60\xa0".to_vec());
+        assert_eq!(
+            image.encode(Format::Pgm),
+            b"P5\n3 2\n255\n\xff\0\0\0\xff\0".to_vec()
+        );
+
+        let mut image = Image::new(1, 1, [10, 20, 30]).scaled(2);
+        image.set((1, 1), [1, 2, 3]);
+        image.set((2, 2), WHITE);
+        assert_eq!((image.width(), image.height()), (2, 2));
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend([10, 20, 30, 10, 20, 30, 10, 20, 30, 1, 2, 3]);
+        assert_eq!(image.encode(Format::Ppm), expected);
+    }
+
+    #[test]
+    fn palettes() {
+        assert_eq!(shade(BLACK, [200, 100, 0], 1, 4), [50, 25, 0]);
+        assert_eq!(shade(BLACK, WHITE, 9, 4), WHITE);
+        assert_eq!(shade(BLACK, WHITE, 0, 0), WHITE);
+        assert_eq!(
+            Format::from_path(Path::new("out/day08.PGM")),
+            Some(Format::Pgm)
+        );
+        assert_eq!(Format::from_path(Path::new("day08.png")), None);
+    }
+}
@@ -0,0 +1,98 @@
+// Ranges of integers with both ends included, like the section assignments of day04.
+use std::fmt;
+
+/// The integers from `start` to `end`, both included, so it is never empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    start: i64,
+    end: i64,
+}
+
+impl Interval {
+    /// None if `start` comes after `end`.
+    pub fn new(start: i64, end: i64) -> Option<Self> {
+        if start > end {
+            return None;
+        }
+        return Some(Interval { start, end });
+    }
+
+    pub fn start(&self) -> i64 {
+        return self.start;
+    }
+
+    pub fn end(&self) -> i64 {
+        return self.end;
+    }
+
+    /// The number of integers in the interval.
+    pub fn length(&self) -> u64 {
+        return self.end.abs_diff(self.start).saturating_add(1);
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        return self.start <= value && value <= self.end;
+    }
+
+    /// Whether every integer of `other` is also in this interval.
+    pub fn contains_interval(&self, other: &Interval) -> bool {
+        return self.start <= other.start && other.end <= self.end;
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        return self.start <= other.end && other.start <= self.end;
+    }
+
+    // overlapping, or right next to each other
+    fn touches(&self, other: &Interval) -> bool {
+        return self.start <= other.end.saturating_add(1)
+            && other.start <= self.end.saturating_add(1);
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        return Interval::new(self.start.max(other.start), self.end.min(other.end));
+    }
+
+    /// The interval covering both, if there is no gap between them.
+    pub fn union(&self, other: &Interval) -> Option<Interval> {
+        if !self.touches(other) {
+            return None;
+        }
+        return Some(Interval {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        });
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}-{}", self.start, self.end);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Interval;
+
+    fn interval(start: i64, end: i64) -> Interval {
+        return Interval::new(start, end).unwrap();
+    }
+
+    #[test]
+    fn intervals() {
+        assert_eq!(Interval::new(3, 2), None);
+        let (a, b) = (interval(2, 6), interval(4, 8));
+        assert_eq!(a.length(), 5);
+        assert!(a.contains(6) && !a.contains(7));
+        assert!(a.overlaps(&b) && !a.contains_interval(&b));
+        assert!(a.contains_interval(&interval(3, 6)));
+        assert_eq!(a.intersection(&b), Some(interval(4, 6)));
+        assert_eq!(a.intersection(&interval(7, 9)), None);
+        assert_eq!(a.union(&b), Some(interval(2, 8)));
+        // no gap between 6 and 7, but one between 6 and 8
+        assert_eq!(a.union(&interval(7, 9)), Some(interval(2, 9)));
+        assert_eq!(a.union(&interval(8, 9)), None);
+        assert_eq!(a.to_string(), "2-6");
+    }
+}
@@ -0,0 +1,69 @@
+// Cooperative cancellation: a run that has limits gets a token for its worker thread, and the
+// solvers call `check` in their long-running loops, which turns a cancelled token (or going over
+// the memory limit) into an error that unwinds the solver through the usual `?`s.
+use crate::common::alloc;
+use crate::common::error::SolveError;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Time,
+    Memory,
+}
+
+/// Limits for a single run; `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    pub time: Option<Duration>,
+    /// bytes
+    pub memory: Option<usize>,
+}
+
+impl Limits {
+    pub fn is_unlimited(&self) -> bool {
+        return self.time.is_none() && self.memory.is_none();
+    }
+}
+
+const RUNNING: u8 = 0;
+const TIMED_OUT: u8 = 1;
+
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicU8>);
+
+impl CancelToken {
+    pub fn time_out(&self) {
+        self.0.store(TIMED_OUT, Ordering::Relaxed);
+    }
+
+    fn check(&self) -> Result<(), SolveError> {
+        return match self.0.load(Ordering::Relaxed) {
+            RUNNING => Ok(()),
+            _ => Err(SolveError::Cancelled(Limit::Time)),
+        };
+    }
+}
+
+thread_local! {
+    static TOKEN: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+/// Makes `token` the one that `check` looks at on this thread.
+pub fn install(token: CancelToken) {
+    TOKEN.with(|current| *current.borrow_mut() = Some(token));
+}
+
+/// Fails once the run on this thread was cancelled or went over its memory limit; always fine
+/// for runs without limits.
+pub fn check() -> Result<(), SolveError> {
+    if alloc::over_limit() {
+        return Err(SolveError::Cancelled(Limit::Memory));
+    }
+    return TOKEN.with(|current| match current.borrow().as_ref() {
+        Some(token) => token.check(),
+        None => Ok(()),
+    });
+}
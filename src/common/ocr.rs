@@ -0,0 +1,167 @@
+// Reads the letters off pictures like the one day10's CRT draws, where '#' is a lit pixel and
+// anything else is dark. Puzzles draw their letters in one of two fonts, one 6 pixels high with
+// letters mostly 4 pixels wide, and one 10 pixels high with letters mostly 6 pixels wide; within
+// a picture, letters are separated by at least one dark column.
+
+const SMALL: &[(char, [&str; 6])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+// every letter in two lines of five rows, which rustfmt would rather pack as tightly as it can
+#[rustfmt::skip]
+const LARGE: &[(char, [&str; 10])] = &[
+    ('A', [
+        "..##..", ".#..#.", "#....#", "#....#", "#....#",
+        "######", "#....#", "#....#", "#....#", "#....#",
+    ]),
+    ('B', [
+        "#####.", "#....#", "#....#", "#....#", "#####.",
+        "#....#", "#....#", "#....#", "#....#", "#####.",
+    ]),
+    ('C', [
+        ".####.", "#....#", "#.....", "#.....", "#.....",
+        "#.....", "#.....", "#.....", "#....#", ".####.",
+    ]),
+    ('E', [
+        "######", "#.....", "#.....", "#.....", "#####.",
+        "#.....", "#.....", "#.....", "#.....", "######",
+    ]),
+    ('F', [
+        "######", "#.....", "#.....", "#.....", "#####.",
+        "#.....", "#.....", "#.....", "#.....", "#.....",
+    ]),
+    ('G', [
+        ".####.", "#....#", "#.....", "#.....", "#.....",
+        "#..###", "#....#", "#....#", "#...##", ".###.#",
+    ]),
+    ('H', [
+        "#....#", "#....#", "#....#", "#....#", "######",
+        "#....#", "#....#", "#....#", "#....#", "#....#",
+    ]),
+    ('J', [
+        "...###", "....#.", "....#.", "....#.", "....#.",
+        "....#.", "....#.", "#...#.", "#...#.", ".###..",
+    ]),
+    ('K', [
+        "#....#", "#...#.", "#..#..", "#.#...", "##....",
+        "##....", "#.#...", "#..#..", "#...#.", "#....#",
+    ]),
+    ('L', [
+        "#.....", "#.....", "#.....", "#.....", "#.....",
+        "#.....", "#.....", "#.....", "#.....", "######",
+    ]),
+    ('N', [
+        "#....#", "##...#", "##...#", "#.#..#", "#.#..#",
+        "#..#.#", "#..#.#", "#...##", "#...##", "#....#",
+    ]),
+    ('P', [
+        "#####.", "#....#", "#....#", "#....#", "#####.",
+        "#.....", "#.....", "#.....", "#.....", "#.....",
+    ]),
+    ('R', [
+        "#####.", "#....#", "#....#", "#....#", "#####.",
+        "#..#..", "#...#.", "#...#.", "#....#", "#....#",
+    ]),
+    ('X', [
+        "#....#", "#....#", ".#..#.", ".#..#.", "..##..",
+        "..##..", ".#..#.", ".#..#.", "#....#", "#....#",
+    ]),
+    ('Z', [
+        "######", ".....#", ".....#", "....#.", "...#..",
+        "..#...", ".#....", "#.....", "#.....", "######",
+    ]),
+];
+
+// the columns of a picture, each as the set of its lit rows
+fn columns(rows: &[&str]) -> Vec<Vec<bool>> {
+    let rows: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    return (0..width)
+        .map(|col| rows.iter().map(|row| row.get(col) == Some(&'#')).collect())
+        .collect();
+}
+
+// the lit columns of every letter, without the dark columns around them
+fn glyphs(columns: &[Vec<bool>]) -> Vec<&[Vec<bool>]> {
+    return columns
+        .split(|column| !column.contains(&true))
+        .filter(|glyph| !glyph.is_empty())
+        .collect();
+}
+
+fn letter<const H: usize>(font: &[(char, [&str; H])], glyph: &[Vec<bool>]) -> Option<char> {
+    return font.iter().find_map(|(letter, rows)| {
+        let columns = columns(rows);
+        let known = glyphs(&columns);
+        return (known.len() == 1 && known[0] == glyph).then_some(*letter);
+    });
+}
+
+/// The letters in `picture`, if it is 6 or 10 rows high (not counting empty lines around it) and
+/// every letter in it is a known one.
+pub fn recognize(picture: &str) -> Option<String> {
+    let rows: Vec<&str> = picture.trim_matches('\n').lines().collect();
+    let columns = columns(&rows);
+    let glyphs = glyphs(&columns);
+    if glyphs.is_empty() {
+        return None;
+    }
+    return glyphs
+        .iter()
+        .map(|glyph| match rows.len() {
+            6 => letter(SMALL, glyph),
+            10 => letter(LARGE, glyph),
+            _ => None,
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod test {
+    use super::{recognize, LARGE};
+
+    #[test]
+    fn small_letters() {
+        let picture = "
+####.#..#.###..####.#....###....##.###..
+#....#..#.#..#....#.#....#..#....#.#..#.
+###..####.###....#..#....#..#....#.#..#.
+#....#..#.#..#..#...#....###.....#.###..
+#....#..#.#..#.#....#....#.#..#..#.#.#..
+####.#..#.###..####.####.#..#..##..#..#.";
+        assert_eq!(recognize(picture), Some("EHBZLRJR".to_string()));
+        // an unknown glyph, and a picture that is not as high as any font
+        assert_eq!(
+            recognize(&picture.replace("####.#..#.###..", "##.#.#..#.###..")),
+            None
+        );
+        assert_eq!(recognize("#..#\n####\n#..#"), None);
+        assert_eq!(recognize("......\n".repeat(6).as_str()), None);
+    }
+
+    #[test]
+    fn large_letters() {
+        let letters = [LARGE[13].1, LARGE[0].1];
+        let picture: Vec<String> = (0..10)
+            .map(|row| format!("{}..{}..", letters[0][row], letters[1][row]))
+            .collect();
+        assert_eq!(recognize(&picture.join("\n")), Some("XA".to_string()));
+    }
+}
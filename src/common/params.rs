@@ -0,0 +1,141 @@
+// Tunable puzzle constants. Every day declares its parameters next to its solvers (listed in
+// runner::DAYS), and the solvers look them up with `get`, which returns the default unless the
+// run on this thread has an override for it; like the cancellation token in limits.rs, overrides
+// are installed on the thread that runs the solvers.
+use crate::common::error::SolveError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Usize,
+    U64,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Kind::Usize => write!(f, "usize"),
+            Kind::U64 => write!(f, "u64"),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub default: u64,
+    /// smallest value the solvers can work with
+    pub min: u64,
+    pub help: &'static str,
+}
+
+/// Values of overridden parameters, by name.
+pub type Overrides = HashMap<&'static str, u64>;
+
+thread_local! {
+    static OVERRIDES: RefCell<Overrides> = RefCell::new(HashMap::new());
+}
+
+/// Makes `overrides` the ones that `get` looks at on this thread.
+pub fn install(overrides: Overrides) {
+    OVERRIDES.with(|current| *current.borrow_mut() = overrides);
+}
+
+/// The overrides installed on this thread, to hand them on to a worker.
+pub fn installed() -> Overrides {
+    return OVERRIDES.with(|current| current.borrow().clone());
+}
+
+/// The value of `param` for the run on this thread.
+pub fn get<T: TryFrom<u64>>(param: &Param) -> Result<T, SolveError> {
+    let value = OVERRIDES.with(|current| current.borrow().get(param.name).copied());
+    let value = value.unwrap_or(param.default);
+    return T::try_from(value).map_err(|_| {
+        SolveError::invalid(format!(
+            "{} = {} is too large for this machine",
+            param.name, value
+        ))
+    });
+}
+
+/// Checks `key=value` assignments against the parameters of a day.
+pub fn parse(params: &[Param], assignments: &[String]) -> Result<Overrides, String> {
+    let mut overrides = HashMap::new();
+    for assignment in assignments {
+        let Some((key, value)) = assignment.split_once('=') else {
+            return Err(format!("expected key=value, got {:?}", assignment));
+        };
+        let Some(param) = params.iter().find(|param| param.name == key.trim()) else {
+            let names: Vec<&str> = params.iter().map(|param| param.name).collect();
+            return Err(match names.is_empty() {
+                true => "this day has no parameters".to_string(),
+                false => format!(
+                    "there is no parameter {:?}, only {}",
+                    key.trim(),
+                    names.join(", ")
+                ),
+            });
+        };
+        let value = value.trim().replace('_', "");
+        let parsed = match param.kind {
+            Kind::Usize => value.parse::<usize>().ok().map(|value| value as u64),
+            Kind::U64 => value.parse::<u64>().ok(),
+        };
+        let Some(parsed) = parsed else {
+            return Err(format!(
+                "{} has to be a {}, got {:?}",
+                param.name, param.kind, value
+            ));
+        };
+        if parsed < param.min {
+            return Err(format!(
+                "{} has to be at least {}, got {}",
+                param.name, param.min, parsed
+            ));
+        }
+        overrides.insert(param.name, parsed);
+    }
+    return Ok(overrides);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{get, install, parse, Kind, Param};
+    use std::collections::HashMap;
+
+    const ROUNDS: Param = Param {
+        name: "rounds",
+        kind: Kind::Usize,
+        default: 20,
+        min: 1,
+        help: "number of rounds",
+    };
+
+    #[test]
+    fn overrides() {
+        let assign = |assignments: &[&str]| {
+            let assignments: Vec<String> = assignments.iter().map(|a| a.to_string()).collect();
+            return parse(&[ROUNDS], &assignments);
+        };
+        assert_eq!(
+            assign(&["rounds=10_000"]),
+            Ok(HashMap::from([("rounds", 10_000)]))
+        );
+        assert!(assign(&["rounds"])
+            .unwrap_err()
+            .starts_with("expected key=value"));
+        assert!(assign(&["turns=3"]).unwrap_err().contains("only rounds"));
+        assert!(assign(&["rounds=-3"])
+            .unwrap_err()
+            .contains("has to be a usize"));
+        assert!(assign(&["rounds=0"]).unwrap_err().contains("at least 1"));
+
+        assert_eq!(get::<usize>(&ROUNDS), Ok(20));
+        install(assign(&["rounds=7"]).unwrap());
+        assert_eq!(get::<usize>(&ROUNDS), Ok(7));
+        install(HashMap::new());
+    }
+}
@@ -0,0 +1,329 @@
+// Helpers for reading puzzle inputs that keep track of where everything is, so that every parse
+// error names the line it was found in, and the column where that helps.
+use crate::common::error::SolveError;
+use crate::common::grid::Grid;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A whole input, or a block of lines from one, that knows which line it starts in.
+#[derive(Debug, Clone, Copy)]
+pub struct Input<'a> {
+    text: &'a str,
+    first_line: usize,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(text: &'a str) -> Self {
+        return Input {
+            text,
+            first_line: 1,
+        };
+    }
+
+    pub fn text(&self) -> &'a str {
+        return self.text;
+    }
+
+    /// Number of the line right after the last one, for errors about missing lines.
+    pub fn end(&self) -> usize {
+        return self.first_line + self.text.lines().count();
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Line<'a>> {
+        let first_line = self.first_line;
+        return self
+            .text
+            .lines()
+            .enumerate()
+            .map(move |(i, text)| Line::new(first_line + i, text));
+    }
+
+    /// The blocks of lines between empty lines. There always is at least one, even if it is
+    /// empty, and every empty line starts a new block, so two of them in a row make an empty one.
+    pub fn blocks(&self) -> impl Iterator<Item = Input<'a>> {
+        let mut rest = Some(*self);
+        return std::iter::from_fn(move || {
+            let input = rest?;
+            let mut offset = 0;
+            for (i, piece) in input.text.split_inclusive('\n').enumerate() {
+                if piece == "\n" || piece == "\r\n" {
+                    rest = Some(Input {
+                        text: &input.text[offset + piece.len()..],
+                        first_line: input.first_line + i + 1,
+                    });
+                    return Some(Input {
+                        text: &input.text[..offset],
+                        first_line: input.first_line,
+                    });
+                }
+                offset += piece.len();
+            }
+            rest = None;
+            return Some(input);
+        });
+    }
+
+    /// Parses a map with one character per cell; see `Grid::parse`.
+    pub fn grid<T>(
+        &self,
+        cell: impl FnMut(char) -> Result<T, String>,
+    ) -> Result<Grid<T>, SolveError> {
+        return Grid::parse(self.text, cell).map_err(|e| match e {
+            SolveError::Parse { line, msg } => SolveError::parse(self.first_line + line - 1, msg),
+            e => e,
+        });
+    }
+
+    /// Every integer in the input, in order.
+    pub fn ints<T>(&self) -> Result<Vec<T>, SolveError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let mut ints = vec![];
+        for line in self.lines() {
+            ints.extend(line.ints()?);
+        }
+        return Ok(ints);
+    }
+}
+
+/// A single line with its 1-based number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(number: usize, text: &'a str) -> Self {
+        return Line { number, text };
+    }
+
+    pub fn error(&self, msg: impl Into<String>) -> SolveError {
+        return SolveError::parse(self.number, msg);
+    }
+
+    /// Parses the whole line as one value.
+    pub fn parse<T>(&self) -> Result<T, SolveError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        return self
+            .text
+            .parse()
+            .map_err(|e| self.error(format!("{}: {:?}", e, self.text)));
+    }
+
+    /// Every integer in the line, in order; a '-' right in front of a number makes it negative.
+    pub fn ints<T>(&self) -> Result<Vec<T>, SolveError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let mut scanner = self.scan();
+        let mut ints = vec![];
+        loop {
+            let skipped = scanner.rest.find(|c: char| c.is_ascii_digit() || c == '-');
+            let Some(skipped) = skipped else {
+                return Ok(ints);
+            };
+            scanner.rest = &scanner.rest[skipped..];
+            if scanner.rest.starts_with('-')
+                && !scanner.rest[1..].starts_with(|c: char| c.is_ascii_digit())
+            {
+                scanner.rest = &scanner.rest[1..];
+                continue;
+            }
+            ints.push(scanner.number()?);
+        }
+    }
+
+    pub fn scan(&self) -> Scanner<'a> {
+        return Scanner {
+            line: *self,
+            rest: self.text,
+        };
+    }
+}
+
+/// Reads a line from left to right, piece by piece, e.g. for "move 1 from 2 to 3":
+///
+/// ```text
+/// let mut scanner = line.scan();
+/// scanner.literal("move ")?;
+/// let amount: usize = scanner.number()?;
+/// scanner.literal(" from ")?;
+/// ...
+/// scanner.end()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    line: Line<'a>,
+    rest: &'a str,
+}
+
+impl<'a> Scanner<'a> {
+    // 1-based column of the next character
+    fn column(&self) -> usize {
+        return self.line.text[..self.line.text.len() - self.rest.len()]
+            .chars()
+            .count()
+            + 1;
+    }
+
+    fn error(&self, expected: &str) -> SolveError {
+        return self.line.error(format!(
+            "expected {} in column {}, got {:?}",
+            expected,
+            self.column(),
+            self.rest
+        ));
+    }
+
+    pub fn literal(&mut self, literal: &str) -> Result<(), SolveError> {
+        match self.rest.strip_prefix(literal) {
+            Some(rest) => self.rest = rest,
+            None => return Err(self.error(&format!("{:?}", literal))),
+        }
+        return Ok(());
+    }
+
+    /// Skips `literal` if the line goes on with it, and tells whether it did.
+    pub fn optional(&mut self, literal: &str) -> bool {
+        return match self.rest.strip_prefix(literal) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        };
+    }
+
+    /// An integer, with a '-' in front of it if it is negative.
+    pub fn number<T>(&mut self) -> Result<T, SolveError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let sign = usize::from(self.rest.starts_with('-'));
+        let length = sign
+            + self.rest[sign..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(self.rest.len() - sign);
+        if length == sign {
+            return Err(self.error("a number"));
+        }
+        let number = &self.rest[..length];
+        let number = number.parse().map_err(|e| {
+            self.line
+                .error(format!("{}: {:?} in column {}", e, number, self.column()))
+        })?;
+        self.rest = &self.rest[length..];
+        return Ok(number);
+    }
+
+    /// Everything up to the next space, which has to be something.
+    pub fn word(&mut self) -> Result<&'a str, SolveError> {
+        let length = self.rest.find(' ').unwrap_or(self.rest.len());
+        if length == 0 {
+            return Err(self.error("a word"));
+        }
+        let word = &self.rest[..length];
+        self.rest = &self.rest[length..];
+        return Ok(word);
+    }
+
+    /// Everything that has not been read yet.
+    pub fn rest(&mut self) -> &'a str {
+        let rest = self.rest;
+        self.rest = "";
+        return rest;
+    }
+
+    pub fn at_end(&self) -> bool {
+        return self.rest.is_empty();
+    }
+
+    pub fn end(&self) -> Result<(), SolveError> {
+        if !self.rest.is_empty() {
+            return Err(self.error("the end of the line"));
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Input, Line};
+    use crate::common::error::SolveError;
+
+    #[test]
+    fn blocks_know_their_lines() {
+        let input = Input::new("a\nb\n\nc\n\n\nd");
+        let blocks: Vec<(usize, &str)> = input
+            .blocks()
+            .map(|block| (block.first_line, block.text()))
+            .collect();
+        assert_eq!(blocks, vec![(1, "a\nb\n"), (4, "c\n"), (6, ""), (7, "d")]);
+        let lines: Vec<Line> = input.blocks().nth(3).unwrap().lines().collect();
+        assert_eq!(lines, vec![Line::new(7, "d")]);
+        assert_eq!(Input::new("").blocks().count(), 1);
+
+        let grid = input
+            .blocks()
+            .nth(1)
+            .unwrap()
+            .grid(|c| Err::<(), _>(format!("{:?}", c)));
+        assert_eq!(grid.unwrap_err(), SolveError::parse(4, "'c'"));
+    }
+
+    #[test]
+    fn ints() {
+        let line = Line::new(3, "Starting items: 79, -98 - 3-4");
+        assert_eq!(line.ints::<i32>(), Ok(vec![79, -98, 3, -4]));
+        assert!(line.ints::<u8>().is_err());
+        assert_eq!(Input::new("1 2\n3").ints::<u64>(), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn scanner() {
+        let line = Line::new(4, "move 1 from 2 to 3");
+        let mut scanner = line.scan();
+        scanner.literal("move ").unwrap();
+        assert_eq!(scanner.number::<usize>(), Ok(1));
+        assert_eq!(
+            scanner.word(),
+            Err(SolveError::parse(
+                4,
+                "expected a word in column 7, got \" from 2 to 3\""
+            ))
+        );
+        scanner.literal(" from ").unwrap();
+        assert_eq!(scanner.number::<i32>(), Ok(2));
+        assert_eq!(
+            scanner.literal(" onto "),
+            Err(SolveError::parse(
+                4,
+                "expected \" onto \" in column 14, got \" to 3\""
+            ))
+        );
+        assert!(!scanner.optional(" from "));
+        assert!(scanner.optional(" to "));
+        assert_eq!(scanner.rest(), "3");
+        assert!(scanner.at_end());
+        assert_eq!(scanner.end(), Ok(()));
+
+        let mut scanner = Line::new(1, "x-3").scan();
+        assert_eq!(
+            scanner.number::<i32>(),
+            Err(SolveError::parse(
+                1,
+                "expected a number in column 1, got \"x-3\""
+            ))
+        );
+        scanner.literal("x").unwrap();
+        assert_eq!(scanner.number::<i32>(), Ok(-3));
+    }
+}
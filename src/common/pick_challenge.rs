@@ -1,83 +1,109 @@
+use crate::common::error::SolveError;
+use crate::common::limits::Limits;
+use crate::common::runner::{self, RunError};
 use crate::solutions;
 use std::error::Error;
-use std::fs;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::time::Instant;
 
-pub fn pick_challenge(day: i32, part: i32, file: String) -> Result<(), Box<dyn Error>> {
-    let data = fs::read_to_string(file)?;
-    match day {
-        1 => match part {
-            1 => println!("result = {}", solutions::day01::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day01::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        2 => match part {
-            1 => println!("result = {}", solutions::day02::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day02::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        3 => match part {
-            1 => println!("result = {}", solutions::day03::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day03::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        4 => match part {
-            1 => println!("result = {}", solutions::day04::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day04::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        5 => match part {
-            1 => println!("result = {}", solutions::day05::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day05::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        6 => match part {
-            1 => println!("result = {}", solutions::day06::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day06::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        7 => match part {
-            1 => println!("result = {}", solutions::day07::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day07::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        8 => match part {
-            1 => println!("result = {}", solutions::day08::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day08::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
+// inputs larger than this are streamed instead of read into memory, if the day supports it
+const STREAMING_THRESHOLD: u64 = 64 * 1024 * 1024;
 
-        9 => match part {
-            1 => println!("result = {}", solutions::day09::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day09::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        10 => match part {
-            1 => println!("result = {}", solutions::day10::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day10::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
-
-        11 => match part {
-            1 => println!("result = {}", solutions::day11::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day11::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
+// returns the answer and its picture, or None if there is no streaming solver for that day and
+// part
+fn stream_challenge(
+    day: i32,
+    part: i32,
+    file: &str,
+) -> Result<Option<(String, Option<String>)>, RunError> {
+    let input = BufReader::new(File::open(file).map_err(SolveError::from)?);
+    let result = match (day, part) {
+        (1, 1) => runner::answer(solutions::day01::solve_p1_stream(input)?),
+        (1, 2) => runner::answer(solutions::day01::solve_p2_stream(input)?),
+        (2, 1) => runner::answer(solutions::day02::solve_p1_stream(input)?),
+        (2, 2) => runner::answer(solutions::day02::solve_p2_stream(input)?),
+        (3, 1) => runner::answer(solutions::day03::solve_p1_stream(input)?),
+        (3, 2) => runner::answer(solutions::day03::solve_p2_stream(input)?),
+        (4, 1) => runner::answer(solutions::day04::solve_p1_stream(input)?),
+        (4, 2) => runner::answer(solutions::day04::solve_p2_stream(input)?),
+        (6, 1) => runner::answer(solutions::day06::solve_p1_stream(input)?),
+        (6, 2) => runner::answer(solutions::day06::solve_p2_stream(input)?),
+        (9, 1) => runner::answer(solutions::day09::solve_p1_stream(input)?),
+        (9, 2) => runner::answer(solutions::day09::solve_p2_stream(input)?),
+        (10, 1) => runner::answer(solutions::day10::solve_p1_stream(input)?),
+        (10, 2) => runner::answer(solutions::day10::solve_p2_stream(input)?),
+        _ => return Ok(None),
+    };
+    return Ok(Some(result));
+}
 
-        12 => match part {
-            1 => println!("result = {}", solutions::day12::solve_p1(&data)),
-            2 => println!("result = {}", solutions::day12::solve_p2(&data)),
-            _ => println!("You chose part {}, but there only 2 parts per day!", part),
-        },
+// prints an answer, with the picture it was read off below it, like day10's CRT screen
+fn print_answer(label: &str, answer: &str, picture: Option<&str>) {
+    println!("{} = {}", label, answer);
+    // undecoded, the answer is the picture itself
+    if let Some(picture) = picture.filter(|_| !answer.contains('\n')) {
+        println!("{}", picture);
+    }
+}
 
-        _ => println!("Have not finished day {} (yet)...", day),
+/// Runs `parts` of `day` on `file` and prints the answers, holding every run to `limits`, whether
+/// the input is read into memory or streamed.
+pub fn pick_challenge(
+    day: i32,
+    parts: &[i32],
+    file: String,
+    timings: bool,
+    limits: &Limits,
+) -> Result<(), Box<dyn Error>> {
+    let label = |part: i32| match parts.len() {
+        1 => "result".to_string(),
+        _ => format!("part {} result", part),
+    };
+    if fs::metadata(&file)?.len() > STREAMING_THRESHOLD {
+        let mut streamed = vec![];
+        for &part in parts {
+            let file = file.clone();
+            let start = Instant::now();
+            match runner::limited(limits, move || stream_challenge(day, part, &file))? {
+                Some(result) => streamed.push((part, result, start.elapsed())),
+                None => break,
+            }
+        }
+        if streamed.len() == parts.len() {
+            for (part, (answer, picture), _) in streamed.iter() {
+                print_answer(&label(*part), answer, picture.as_deref());
+            }
+            if timings {
+                // a streamed part reads the file as it solves, so there is no parse time of its own
+                for (part, _, time) in streamed.iter() {
+                    eprintln!("part {} took {:?}, parsing included (streamed)", part, time);
+                }
+            }
+            return Ok(());
+        }
+    }
+    let data = fs::read_to_string(file)?;
+    let run = match runner::run_limited(day, parts, &data, limits) {
+        Ok(run) => run,
+        Err(e @ (RunError::UnknownDay(_) | RunError::UnknownPart(_))) => {
+            println!("{}", e);
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
     };
+    for result in run.parts.iter() {
+        print_answer(
+            &label(result.part),
+            &result.answer,
+            result.picture.as_deref(),
+        );
+    }
+    if timings {
+        eprintln!("parse took {:?}", run.parse_time);
+        for result in run.parts.iter() {
+            eprintln!("part {} took {:?}", result.part, result.solve_time);
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,145 @@
+// Python extension module; build it with `maturin develop` (see pyproject.toml), then
+// `import aoc2022` and call `aoc2022.solve(7, 1, open("data/day07.txt").read())`.
+use crate::common::runner;
+use crate::solutions::{day07, day11};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// A file or directory from day07's file system; directories may be empty. Sizes of directories
+/// include everything below them.
+#[pyclass(module = "aoc2022", frozen)]
+#[derive(Clone)]
+struct Entry {
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get)]
+    size: usize,
+    #[pyo3(get)]
+    children: Vec<Entry>,
+    is_dir: bool,
+}
+
+#[pymethods]
+impl Entry {
+    fn is_dir(&self) -> bool {
+        return self.is_dir;
+    }
+
+    fn __repr__(&self) -> String {
+        return format!(
+            "Entry(name={:?}, size={}, children={})",
+            self.name,
+            self.size,
+            self.children.len()
+        );
+    }
+}
+
+impl Entry {
+    fn new(fs: &day07::FileSystem, id: day07::NodeId) -> Self {
+        let node = fs.node(id);
+        return Entry {
+            name: node.name.clone(),
+            size: node.size,
+            children: node
+                .children
+                .iter()
+                .map(|child| Entry::new(fs, *child))
+                .collect(),
+            is_dir: node.is_dir,
+        };
+    }
+}
+
+/// One of day11's monkeys as it is described in the input; `operation` is written like in the
+/// input, e.g. "old * 19".
+#[pyclass(module = "aoc2022", get_all, frozen)]
+struct Monkey {
+    items: Vec<u64>,
+    operation: String,
+    test_value: u64,
+    throw_if_true_to: usize,
+    throw_if_false_to: usize,
+}
+
+#[pymethods]
+impl Monkey {
+    fn __repr__(&self) -> String {
+        return format!(
+            "Monkey(items={:?}, operation={:?}, test_value={}, throw_if_true_to={}, \
+             throw_if_false_to={})",
+            self.items,
+            self.operation,
+            self.test_value,
+            self.throw_if_true_to,
+            self.throw_if_false_to
+        );
+    }
+}
+
+impl From<&day11::Monkey> for Monkey {
+    fn from(monkey: &day11::Monkey) -> Self {
+        return Monkey {
+            items: monkey.items.clone(),
+            operation: match monkey.op {
+                day11::Operation::Add(x) => format!("old + {}", x),
+                day11::Operation::Mul(x) => format!("old * {}", x),
+                day11::Operation::Sqr => "old * old".to_string(),
+            },
+            test_value: monkey.test_value,
+            throw_if_true_to: monkey.throw_if_true_to,
+            throw_if_false_to: monkey.throw_if_false_to,
+        };
+    }
+}
+
+/// Solves one part of a day; unknown days or parts and bad inputs raise a ValueError.
+#[pyfunction]
+fn solve(day: i32, part: i32, input: &str) -> PyResult<String> {
+    let mut run =
+        runner::run(day, &[part], input).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    return Ok(run.parts.remove(0).answer);
+}
+
+/// Parses a day07 terminal session into its root directory.
+#[pyfunction]
+fn parse_day07(input: &str) -> PyResult<Entry> {
+    let fs = day07::parse(input).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    return Ok(Entry::new(&fs, day07::FileSystem::ROOT));
+}
+
+/// Parses day11's notes into the list of monkeys.
+#[pyfunction]
+fn parse_day11(input: &str) -> PyResult<Vec<Monkey>> {
+    let monkeys = day11::parse(input).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    return Ok(monkeys.iter().map(Monkey::from).collect());
+}
+
+#[pymodule]
+fn aoc2022(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_day07, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_day11, m)?)?;
+    m.add_class::<Entry>()?;
+    m.add_class::<Monkey>()?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+    use super::Entry;
+    use crate::solutions::day07;
+
+    #[test]
+    fn empty_directories() {
+        let fs = day07::parse("$ cd /\n$ ls\ndir empty\n100 file").unwrap();
+        let root = Entry::new(&fs, day07::FileSystem::ROOT);
+        let kinds: Vec<(&str, bool, usize)> = root
+            .children
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.is_dir(), entry.children.len()))
+            .collect();
+        assert!(root.is_dir());
+        assert_eq!(kinds, vec![("empty", true, 0), ("file", false, 0)]);
+    }
+}
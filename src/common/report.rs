@@ -0,0 +1,297 @@
+use crate::common::alloc::{self, Stats};
+use crate::common::runner::{self, Day, Run, DAYS};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            _ => Err(format!("expected markdown or html, got {:?}", s)),
+        };
+    }
+}
+
+pub enum Outcome {
+    /// the allocation stats cover parsing and all parts, and stay at 0 without the counting
+    /// allocator
+    Solved(Run, Stats),
+    Failed(String),
+    /// there is no input file for that day
+    Missing(String),
+}
+
+pub struct DayReport {
+    pub day: &'static Day,
+    pub outcome: Outcome,
+}
+
+/// Runs the finished parts of each of `days` (all of them if empty) on `data_dir/dayNN.txt`.
+pub fn run_report(days: &[i32], data_dir: &Path) -> Vec<DayReport> {
+    return DAYS
+        .iter()
+        .filter(|day| days.is_empty() || days.contains(&day.day))
+        .map(|day| {
+            let path = data_dir.join(format!("day{:02}.txt", day.day));
+            let outcome = match fs::read_to_string(&path) {
+                Err(e) => Outcome::Missing(format!("could not read {}: {}", path.display(), e)),
+                Ok(data) => {
+                    let parts: Vec<i32> = (1..=day.stars as i32).collect();
+                    alloc::measure(None);
+                    match runner::run(day.day, &parts, &data) {
+                        Ok(run) => Outcome::Solved(run, alloc::stats()),
+                        Err(e) => Outcome::Failed(e.to_string()),
+                    }
+                }
+            };
+            DayReport { day, outcome }
+        })
+        .collect();
+}
+
+fn status(report: &DayReport) -> String {
+    return match &report.outcome {
+        Outcome::Solved(..) if report.day.stars < 2 => "solved, part 2 not done yet".to_string(),
+        Outcome::Solved(..) => "solved".to_string(),
+        Outcome::Failed(e) => format!("failed: {}", e),
+        Outcome::Missing(e) => format!("no input: {}", e),
+    };
+}
+
+fn micros(duration: Duration) -> String {
+    return format!("{}µs", duration.as_micros());
+}
+
+fn bytes(bytes: isize) -> String {
+    return match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MiB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KiB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    };
+}
+
+// a block of rows with a header, which both formats lay out as a table
+struct Table {
+    title: &'static str,
+    header: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+// the day10 CRT output, or any other picture that goes with an answer
+struct Picture {
+    title: String,
+    text: String,
+}
+
+fn tables(reports: &[DayReport]) -> (Vec<Table>, Vec<Picture>) {
+    let mut answers = Table {
+        title: "Answers",
+        header: vec!["day", "puzzle", "stars", "status", "part 1", "part 2"],
+        rows: vec![],
+    };
+    let mut timings = Table {
+        title: "Timings",
+        header: vec!["day", "parse", "part 1", "part 2", "total"],
+        rows: vec![],
+    };
+    let mut allocations = Table {
+        title: "Allocations",
+        header: vec!["day", "allocations", "peak memory"],
+        rows: vec![],
+    };
+    let mut pictures = vec![];
+    let mut total = Duration::ZERO;
+    for report in reports {
+        let day = report.day.day.to_string();
+        let mut row = vec![
+            day.clone(),
+            report.day.title.to_string(),
+            "★".repeat(report.day.stars as usize),
+            status(report),
+        ];
+        let Outcome::Solved(run, stats) = &report.outcome else {
+            row.extend(["".to_string(), "".to_string()]);
+            answers.rows.push(row);
+            continue;
+        };
+        let mut times = vec![day.clone(), micros(run.parse_time)];
+        for part in 1..=2 {
+            match run.parts.iter().find(|result| result.part == part) {
+                Some(result) if result.picture.is_some() => {
+                    let title = format!("Day {}, part {}", day, part);
+                    // undecoded, the answer is the picture itself, which does not fit into a cell
+                    row.push(match result.answer.contains('\n') {
+                        true => format!("see \"{}\"", title),
+                        false => format!("{}, see \"{}\"", result.answer, title),
+                    });
+                    times.push(micros(result.solve_time));
+                    pictures.push(Picture {
+                        title,
+                        text: result.picture.clone().unwrap(),
+                    });
+                }
+                Some(result) => {
+                    row.push(result.answer.clone());
+                    times.push(micros(result.solve_time));
+                }
+                None => {
+                    row.push("".to_string());
+                    times.push("".to_string());
+                }
+            }
+        }
+        let day_total = run.parse_time
+            + run
+                .parts
+                .iter()
+                .map(|result| result.solve_time)
+                .sum::<Duration>();
+        total += day_total;
+        times.push(micros(day_total));
+        answers.rows.push(row);
+        timings.rows.push(times);
+        allocations
+            .rows
+            .push(vec![day, stats.allocations.to_string(), bytes(stats.peak)]);
+    }
+    timings.rows.push(vec![
+        "all".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        micros(total),
+    ]);
+    return (vec![answers, timings, allocations], pictures);
+}
+
+/// Lays out the reports as a Markdown page, or as an HTML page that carries its own styles.
+pub fn render(reports: &[DayReport], format: Format) -> String {
+    let (tables, pictures) = tables(reports);
+    let solved = reports
+        .iter()
+        .filter(|report| matches!(report.outcome, Outcome::Solved(..)))
+        .count();
+    let summary = format!("{} of {} days solved.", solved, reports.len());
+    return match format {
+        Format::Markdown => markdown(&summary, &tables, &pictures),
+        Format::Html => html(&summary, &tables, &pictures),
+    };
+}
+
+fn markdown(summary: &str, tables: &[Table], pictures: &[Picture]) -> String {
+    let cell = |s: &str| s.replace('|', "\\|");
+    let mut page = format!("# Advent of Code 2022\n\n{}\n", summary);
+    for table in tables {
+        let _ = write!(
+            page,
+            "\n## {}\n\n| {} |\n",
+            table.title,
+            table.header.join(" | ")
+        );
+        let _ = writeln!(page, "|{}", "---|".repeat(table.header.len()));
+        for row in table.rows.iter() {
+            let cells: Vec<String> = row.iter().map(|s| cell(s)).collect();
+            let _ = writeln!(page, "| {} |", cells.join(" | "));
+        }
+    }
+    for picture in pictures {
+        let _ = write!(
+            page,
+            "\n## {}\n\n```text\n{}\n```\n",
+            picture.title, picture.text
+        );
+    }
+    return page;
+}
+
+fn escape(s: &str) -> String {
+    return s
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: 2em auto; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.75em; text-align: left; }
+th { background: #eee; }
+pre { background: #f6f6f6; padding: 1em; line-height: 1.1; }";
+
+fn html(summary: &str, tables: &[Table], pictures: &[Picture]) -> String {
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Advent of Code 2022</title>\n\
+         <style>\n{}\n</style>\n</head>\n<body>\n<h1>Advent of Code 2022</h1>\n<p>{}</p>\n",
+        STYLE,
+        escape(summary)
+    );
+    for table in tables {
+        let _ = writeln!(page, "<h2>{}</h2>\n<table>", escape(table.title));
+        let header: Vec<String> = table
+            .header
+            .iter()
+            .map(|s| format!("<th>{}</th>", escape(s)))
+            .collect();
+        let _ = writeln!(page, "<tr>{}</tr>", header.concat());
+        for row in table.rows.iter() {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|s| format!("<td>{}</td>", escape(s)))
+                .collect();
+            let _ = writeln!(page, "<tr>{}</tr>", cells.concat());
+        }
+        page.push_str("</table>\n");
+    }
+    for picture in pictures {
+        let _ = writeln!(
+            page,
+            "<h2>{}</h2>\n<pre>{}</pre>",
+            escape(&picture.title),
+            escape(&picture.text)
+        );
+    }
+    page.push_str("</body>\n</html>\n");
+    return page;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render, run_report, Format};
+    use std::path::Path;
+
+    #[test]
+    fn reports() {
+        let reports = run_report(&[2, 10], Path::new("data/examples"));
+        let page = render(&reports, Format::Markdown);
+        assert!(page.contains("| 2 | Rock Paper Scissors | ★★ | solved | 15 | 12 |"));
+        assert!(page
+            .contains("## Day 10, part 2\n\n```text\n##..##..##..##..##..##..##..##..##..##..\n"));
+        assert!(page.contains("2 of 2 days solved."));
+
+        // the letters on the real screen are read off, and the screen is still shown
+        let page = render(&run_report(&[10], Path::new("data")), Format::Markdown);
+        assert!(page.contains(
+            "| 10 | Cathode-Ray Tube | ★★ | solved | 12640 | EHBZLRJR, see \"Day 10, part 2\" |"
+        ));
+        assert!(page
+            .contains("## Day 10, part 2\n\n```text\n####.#..#.###..####.#....###....##.###..\n"));
+
+        let missing = run_report(&[1], Path::new("does/not/exist"));
+        let page = render(&missing, Format::Html);
+        assert!(page.contains("<td>no input: could not read does/not/exist/day01.txt"));
+        assert!(page.contains("0 of 1 days solved."));
+    }
+}
@@ -0,0 +1,406 @@
+use crate::common::alloc;
+use crate::common::error::SolveError;
+use crate::common::image::Image;
+use crate::common::limits::{self, CancelToken, Limit, Limits};
+use crate::common::params::{self, Param};
+use crate::solutions;
+use std::error::Error;
+use std::fmt;
+use std::panic;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// how long a timed out run gets to notice its cancellation before we stop waiting for it
+const CANCEL_GRACE: Duration = Duration::from_millis(500);
+
+/// Result of running some parts of a day on a single parsed input.
+#[derive(Debug)]
+pub struct Run {
+    pub parse_time: Duration,
+    pub parts: Vec<PartResult>,
+}
+
+#[derive(Debug)]
+pub struct PartResult {
+    pub part: i32,
+    pub answer: String,
+    /// what the answer was read off, like day10's CRT screen, with one line per row
+    pub picture: Option<String>,
+    pub solve_time: Duration,
+}
+
+/// What a part can be answered with: anything printable, some of it with a picture that goes
+/// with it.
+pub trait Answer: fmt::Display {
+    fn picture(&self) -> Option<String> {
+        return None;
+    }
+}
+
+impl Answer for i32 {}
+impl Answer for usize {}
+impl Answer for String {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunError {
+    UnknownDay(i32),
+    UnknownPart(i32),
+    Solve(SolveError),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::UnknownDay(day) => write!(f, "Have not finished day {} (yet)...", day),
+            RunError::UnknownPart(part) => write!(
+                f,
+                "You chose part {}, but there only 2 parts per day!",
+                part
+            ),
+            RunError::Solve(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for RunError {}
+
+impl From<SolveError> for RunError {
+    fn from(e: SolveError) -> Self {
+        return RunError::Solve(e);
+    }
+}
+
+/// A day that has a solver, with the puzzle title and the number of parts solved so far.
+#[derive(Debug)]
+pub struct Day {
+    pub day: i32,
+    pub title: &'static str,
+    pub stars: u8,
+    /// constants of the puzzle that can be overridden with `--param`
+    pub params: &'static [Param],
+}
+
+pub const DAYS: [Day; 12] = [
+    Day {
+        day: 1,
+        title: "Calorie Counting",
+        stars: 2,
+        params: &[],
+    },
+    Day {
+        day: 2,
+        title: "Rock Paper Scissors",
+        stars: 2,
+        params: &[],
+    },
+    Day {
+        day: 3,
+        title: "Rucksack Reorganization",
+        stars: 2,
+        params: &[],
+    },
+    Day {
+        day: 4,
+        title: "Camp Cleanup",
+        stars: 2,
+        params: &[],
+    },
+    Day {
+        day: 5,
+        title: "Supply Stacks",
+        stars: 2,
+        params: &[],
+    },
+    Day {
+        day: 6,
+        title: "Tuning Trouble",
+        stars: 2,
+        params: solutions::day06::PARAMS,
+    },
+    Day {
+        day: 7,
+        title: "No Space Left On Device",
+        stars: 2,
+        params: solutions::day07::PARAMS,
+    },
+    Day {
+        day: 8,
+        title: "Treetop Tree House",
+        stars: 2,
+        params: &[],
+    },
+    Day {
+        day: 9,
+        title: "Rope Bridge",
+        stars: 2,
+        params: solutions::day09::PARAMS,
+    },
+    Day {
+        day: 10,
+        title: "Cathode-Ray Tube",
+        stars: 2,
+        params: solutions::day10::PARAMS,
+    },
+    Day {
+        day: 11,
+        title: "Monkey in the Middle",
+        stars: 2,
+        params: solutions::day11::PARAMS,
+    },
+    // part 2 is not done yet
+    Day {
+        day: 12,
+        title: "Hill Climbing Algorithm",
+        stars: 1,
+        params: &[],
+    },
+];
+
+/// `answer` as it gets printed, together with the picture that goes with it, if any.
+pub fn answer(answer: impl Answer) -> (String, Option<String>) {
+    return (answer.to_string(), answer.picture());
+}
+
+// parses the input once, then solves every requested part on it
+macro_rules! run_day {
+    ($day:ident, $parts:expr, $data:expr) => {{
+        let start = Instant::now();
+        let input = solutions::$day::parse($data)?;
+        let parse_time = start.elapsed();
+        let mut results = vec![];
+        for &part in $parts {
+            let start = Instant::now();
+            let (answer, picture) = match part {
+                1 => answer(solutions::$day::solve_p1(&input)?),
+                _ => answer(solutions::$day::solve_p2(&input)?),
+            };
+            results.push(PartResult {
+                part,
+                answer,
+                picture,
+                solve_time: start.elapsed(),
+            });
+        }
+        Run {
+            parse_time,
+            parts: results,
+        }
+    }};
+}
+
+/// Runs the given parts of a day on `data`, parsing it only once.
+pub fn run(day: i32, parts: &[i32], data: &str) -> Result<Run, RunError> {
+    if let Some(part) = parts.iter().find(|part| !(1..=2).contains(*part)) {
+        return Err(RunError::UnknownPart(*part));
+    }
+    let run = match day {
+        1 => run_day!(day01, parts, data),
+        2 => run_day!(day02, parts, data),
+        3 => run_day!(day03, parts, data),
+        4 => run_day!(day04, parts, data),
+        5 => run_day!(day05, parts, data),
+        6 => run_day!(day06, parts, data),
+        7 => run_day!(day07, parts, data),
+        8 => run_day!(day08, parts, data),
+        9 => run_day!(day09, parts, data),
+        10 => run_day!(day10, parts, data),
+        11 => run_day!(day11, parts, data),
+        12 => run_day!(day12, parts, data),
+        _ => return Err(RunError::UnknownDay(day)),
+    };
+    return Ok(run);
+}
+
+/// A picture of what `day` makes of `data`, or None if the day has nothing to draw.
+pub fn render(day: i32, data: &str) -> Result<Option<Image>, RunError> {
+    let image = match day {
+        8 => solutions::day08::render(&solutions::day08::parse(data)?)?,
+        10 => solutions::day10::render(&solutions::day10::parse(data)?)?,
+        12 => solutions::day12::render(&solutions::day12::parse(data)?)?,
+        _ if DAYS.iter().any(|d| d.day == day) => return Ok(None),
+        _ => return Err(RunError::UnknownDay(day)),
+    };
+    return Ok(Some(image));
+}
+
+/// Like `run`, but on a worker thread that is cancelled once it goes over `limits`; see `limited`.
+pub fn run_limited(day: i32, parts: &[i32], data: &str, limits: &Limits) -> Result<Run, RunError> {
+    if limits.is_unlimited() {
+        return run(day, parts, data);
+    }
+    let (parts, data) = (parts.to_vec(), data.to_string());
+    return limited(limits, move || run(day, &parts, &data));
+}
+
+/// Calls `solve` on a worker thread that is cancelled once it goes over `limits`. A solver that
+/// does not notice its cancellation within a grace period is left behind, still reported as
+/// timed out. The worker gets the parameter overrides of the calling thread.
+pub fn limited<T: Send + 'static>(
+    limits: &Limits,
+    solve: impl FnOnce() -> Result<T, RunError> + Send + 'static,
+) -> Result<T, RunError> {
+    if limits.is_unlimited() {
+        return solve();
+    }
+    let token = CancelToken::default();
+    let (sender, receiver) = mpsc::channel();
+    let worker = {
+        let (token, memory) = (token.clone(), limits.memory);
+        let overrides = params::installed();
+        thread::spawn(move || {
+            limits::install(token);
+            params::install(overrides);
+            alloc::measure(memory);
+            let _ = sender.send(solve());
+        })
+    };
+    let result = match limits.time {
+        Some(time) => receiver.recv_timeout(time),
+        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    return match result {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            token.time_out();
+            let _ = receiver.recv_timeout(CANCEL_GRACE);
+            Err(RunError::Solve(SolveError::Cancelled(Limit::Time)))
+        }
+        // the worker hung up without an answer, so it panicked; pass that on like `solve` would
+        Err(RecvTimeoutError::Disconnected) => match worker.join() {
+            Err(payload) => panic::resume_unwind(payload),
+            Ok(()) => unreachable!("the worker always sends its result"),
+        },
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::{limited, render, run, run_limited, RunError};
+    use crate::common::error::SolveError;
+    use crate::common::limits::{self, CancelToken, Limit, Limits};
+    use crate::common::params;
+    use crate::solutions;
+    use std::io::Cursor;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_once_for_both_parts() {
+        let run = run(2, &[1, 2], "A Y\nB X\nC Z").unwrap();
+        let answers: Vec<_> = run
+            .parts
+            .iter()
+            .map(|p| (p.part, p.answer.as_str()))
+            .collect();
+        assert_eq!(answers, vec![(1, "15"), (2, "12")]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(run(13, &[1], "").err(), Some(RunError::UnknownDay(13)));
+        assert_eq!(run(1, &[3], "").err(), Some(RunError::UnknownPart(3)));
+        assert_eq!(
+            run(2, &[1], "A Q").err(),
+            Some(RunError::Solve(SolveError::parse(
+                1,
+                "expected e.g. \"A X\", got \"A Q\""
+            )))
+        );
+    }
+
+    #[test]
+    fn pictures() {
+        let forest = render(8, include_str!("../../data/examples/day08.txt"))
+            .unwrap()
+            .unwrap();
+        assert_eq!((forest.width(), forest.height()), (5, 5));
+        assert_eq!(render(1, "1\n\n2").unwrap(), None);
+        assert_eq!(render(13, "").err(), Some(RunError::UnknownDay(13)));
+    }
+
+    #[test]
+    fn cancelled_runs_stop() {
+        let cancelled = thread::spawn(|| {
+            let token = CancelToken::default();
+            token.time_out();
+            limits::install(token);
+            return run(11, &[2], include_str!("../../data/examples/day11.txt"));
+        });
+        let timed_out = Some(RunError::Solve(SolveError::Cancelled(Limit::Time)));
+        assert_eq!(cancelled.join().unwrap().err(), timed_out);
+
+        // no marker anywhere, so day06 has to look at every position
+        let limits = Limits {
+            time: Some(Duration::from_millis(10)),
+            memory: None,
+        };
+        assert_eq!(
+            run_limited(6, &[2], &"a".repeat(50_000_000), &limits).err(),
+            timed_out
+        );
+    }
+
+    // `limited` gives up on a worker as timed out either way, so the workers hand over what they
+    // made of their input themselves, to show that they stopped reading it
+    #[test]
+    fn streamed_runs_stop() {
+        let limits = Limits {
+            time: Some(Duration::from_millis(10)),
+            memory: None,
+        };
+        let timed_out = SolveError::Cancelled(Limit::Time);
+        let stops = |solve: Box<dyn FnOnce() -> Result<(), SolveError> + Send>| {
+            let (sender, receiver) = mpsc::channel();
+            let run = limited(&limits, move || {
+                let result = solve();
+                let _ = sender.send(result.clone());
+                return Ok(result?);
+            });
+            assert_eq!(run.err(), Some(RunError::Solve(timed_out.clone())));
+            // a worker that read on until the end would have an answer, or no marker, to show
+            let stopped = receiver.recv_timeout(Duration::from_secs(10));
+            assert_eq!(stopped, Ok(Err(timed_out.clone())));
+        };
+        // no marker anywhere for day06, and a single elf for day01, so both read until the end
+        let markers = Cursor::new("a".repeat(50_000_000));
+        stops(Box::new(move || {
+            return solutions::day06::solve_p2_stream(markers).map(|_| ());
+        }));
+        let calories = Cursor::new("1\n".repeat(20_000_000));
+        stops(Box::new(move || {
+            return solutions::day01::solve_p1_stream(calories).map(|_| ());
+        }));
+    }
+
+    #[test]
+    fn params_reach_the_worker() {
+        let data = include_str!("../../data/examples/day09.txt");
+        let overrides = params::parse(super::DAYS[8].params, &["long_rope=2".to_string()]).unwrap();
+        params::install(overrides);
+        let limits = Limits {
+            time: Some(Duration::from_secs(10)),
+            memory: None,
+        };
+        let run = run_limited(9, &[1, 2], data, &limits).unwrap();
+        assert_eq!(run.parts[0].answer, run.parts[1].answer);
+    }
+
+    // with less relief, the worry levels of day11's part 1 explode on the real input, while more
+    // rounds are fine as long as the relief keeps them down
+    #[test]
+    fn overridden_params_cannot_overflow() {
+        let data = include_str!("../../data/day11.txt");
+        let install = |overrides: &[&str]| {
+            let overrides: Vec<String> = overrides.iter().map(|o| o.to_string()).collect();
+            params::install(params::parse(super::DAYS[10].params, &overrides).unwrap());
+        };
+        install(&["relief=2"]);
+        let overflow =
+            SolveError::invalid(format!("the worry level of an item outgrows {}", u64::MAX));
+        assert_eq!(run(11, &[1], data).err(), Some(RunError::Solve(overflow)));
+        install(&["relief=3", "rounds_p1=10000"]);
+        assert!(run(11, &[1], data).is_ok());
+    }
+}
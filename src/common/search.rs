@@ -0,0 +1,218 @@
+// Searches through graphs that are given by a successor function, so that the nodes can be
+// anything hashable, from grid points to whole puzzle states. All searches start from any number
+// of nodes at once, stop at the first node that `is_goal` holds for (pass `|_| false` to search
+// the whole graph), and check for cancellation as they go.
+use crate::common::error::SolveError;
+use crate::common::limits;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+pub type Cost = u64;
+
+/// What a search found: the distance to every node it reached, and the way back from each.
+#[derive(Debug, Clone)]
+pub struct Search<N> {
+    distances: HashMap<N, Cost>,
+    parents: HashMap<N, N>,
+    goal: Option<N>,
+}
+
+impl<N: Eq + Hash + Clone> Search<N> {
+    fn new() -> Self {
+        return Search {
+            distances: HashMap::new(),
+            parents: HashMap::new(),
+            goal: None,
+        };
+    }
+
+    // false if `node` is a start already
+    fn start(&mut self, node: N) -> bool {
+        if self.distances.contains_key(&node) {
+            return false;
+        }
+        self.distances.insert(node, 0);
+        return true;
+    }
+
+    // records the way to `next` through `node` if it is shorter than the one known so far
+    fn relax(&mut self, node: &N, next: &N, cost: Cost) -> bool {
+        if self.distances.get(next).is_some_and(|known| *known <= cost) {
+            return false;
+        }
+        self.distances.insert(next.clone(), cost);
+        self.parents.insert(next.clone(), node.clone());
+        return true;
+    }
+
+    /// The goal the search stopped at, and its distance from the nearest start.
+    pub fn goal(&self) -> Option<(&N, Cost)> {
+        let goal = self.goal.as_ref()?;
+        return Some((goal, self.distances[goal]));
+    }
+
+    /// The distance of `node` from the nearest start. When the search stopped at a goal, nodes
+    /// that are further away than the goal may not have their shortest distance yet.
+    pub fn distance(&self, node: &N) -> Option<Cost> {
+        return self.distances.get(node).copied();
+    }
+
+    pub fn distances(&self) -> &HashMap<N, Cost> {
+        return &self.distances;
+    }
+
+    /// The nodes from a start to `node`, both included.
+    pub fn path(&self, node: &N) -> Option<Vec<N>> {
+        if !self.distances.contains_key(node) {
+            return None;
+        }
+        let mut path = vec![node.clone()];
+        while let Some(parent) = self.parents.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        return Some(path);
+    }
+}
+
+/// Breadth first search, for graphs where every step costs 1.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Result<Search<N>, SolveError>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if search.start(start.clone()) {
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        limits::check()?;
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+        let cost = search.distances[&node] + 1;
+        for next in successors(&node) {
+            // the first way to a node is a shortest one
+            if !search.distances.contains_key(&next) && search.relax(&node, &next, cost) {
+                queue.push_back(next);
+            }
+        }
+    }
+    return Ok(search);
+}
+
+/// Dijkstra's algorithm, for graphs where `successors` yields every next node with the cost of
+/// the step there.
+pub fn dijkstra<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Result<Search<N>, SolveError>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, Cost)>,
+{
+    return astar(starts, successors, |_| 0, is_goal);
+}
+
+/// A*, which is Dijkstra's algorithm looking at the nodes with the smallest cost plus `heuristic`
+/// first. The distance to the goal is only the shortest one if `heuristic` never overestimates
+/// the cost of getting to a goal.
+pub fn astar<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> Cost,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Result<Search<N>, SolveError>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, Cost)>,
+{
+    let mut search = Search::new();
+    // the queue holds indices into `nodes`, so that nodes do not have to be ordered
+    let mut nodes = vec![];
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if search.start(start.clone()) {
+            queue.push(Reverse((heuristic(&start), 0, nodes.len())));
+            nodes.push(start);
+        }
+    }
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        limits::check()?;
+        let node = nodes[index].clone();
+        // a shorter way to this node has been queued since
+        if search.distances[&node] < cost {
+            continue;
+        }
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            if search.relax(&node, &next, next_cost) {
+                queue.push(Reverse((
+                    next_cost + heuristic(&next),
+                    next_cost,
+                    nodes.len(),
+                )));
+                nodes.push(next);
+            }
+        }
+    }
+    return Ok(search);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{astar, bfs, dijkstra, Cost};
+
+    // from n, one can go to n + 1 for 1 and to 2n for 3
+    fn successors(n: &u32) -> Vec<(u32, Cost)> {
+        return vec![(n + 1, 1), (n * 2, 3)];
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let search = bfs(
+            [1],
+            |n| successors(n).into_iter().map(|(n, _)| n),
+            |n| *n == 20,
+        )
+        .unwrap();
+        assert_eq!(search.goal(), Some((&20, 5)));
+        assert_eq!(search.path(&20).unwrap().len(), 6);
+
+        let search = dijkstra([1], successors, |n| *n == 20).unwrap();
+        assert_eq!(search.goal(), Some((&20, 10)));
+        assert_eq!(search.path(&20), Some(vec![1, 2, 3, 4, 5, 10, 20]));
+
+        // every number short of 20 needs at least one more step
+        let search = astar([1], successors, |n| Cost::from(*n < 20), |n| *n == 20).unwrap();
+        assert_eq!(search.goal(), Some((&20, 10)));
+
+        // from several starts at once, without a goal, within a bounded graph
+        let search = dijkstra(
+            [7, 1],
+            |n| successors(n).into_iter().filter(|(n, _)| *n <= 10),
+            |_| false,
+        )
+        .unwrap();
+        assert_eq!(search.goal(), None);
+        assert_eq!(search.distance(&8), Some(1));
+        assert_eq!(search.distance(&6), Some(5));
+        assert_eq!(search.distances().len(), 10);
+        assert_eq!(search.path(&7), Some(vec![7]));
+        assert_eq!(search.path(&11), None);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::common::error::SolveError;
+use crate::common::limits::{Limit, Limits};
+use crate::common::runner::{self, RunError};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io::Read;
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub address: String,
+    /// requests with larger bodies are rejected with 413
+    pub max_body: usize,
+    /// solves that take longer than this are answered with 504, and those that need more memory
+    /// with 503
+    pub limits: Limits,
+}
+
+/// Serves `POST /solve/{day}/{part}` with the puzzle input as the body until the process is killed.
+/// Every request is handled on its own thread.
+pub fn serve(config: Config) -> Result<(), Box<dyn Error>> {
+    let server = Server::http(&config.address).map_err(|e| e as Box<dyn Error>)?;
+    eprintln!("listening on http://{}", server.server_addr());
+    for request in server.incoming_requests() {
+        let config = config.clone();
+        thread::spawn(move || handle(request, &config));
+    }
+    return Ok(());
+}
+
+fn handle(mut request: Request, config: &Config) {
+    let (status, body) = match route(request.method(), request.url()) {
+        Err(response) => response,
+        Ok(_)
+            if request
+                .body_length()
+                .is_some_and(|length| length > config.max_body) =>
+        {
+            too_large(config)
+        }
+        Ok((day, part)) => {
+            // the length header is optional, so cap the read as well
+            let mut body = vec![];
+            match request
+                .as_reader()
+                .take(config.max_body as u64 + 1)
+                .read_to_end(&mut body)
+            {
+                Err(e) => (
+                    400,
+                    json!({ "error": format!("could not read the request: {}", e) }),
+                ),
+                Ok(_) if body.len() > config.max_body => too_large(config),
+                Ok(_) => solve(day, part, body, &config.limits),
+            }
+        }
+    };
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        eprintln!("could not send the response: {}", e);
+    }
+}
+
+fn too_large(config: &Config) -> (u16, Value) {
+    return (
+        413,
+        json!({ "error": format!("the input must not be larger than {} bytes", config.max_body) }),
+    );
+}
+
+// picks day and part out of `/solve/{day}/{part}`
+fn route(method: &Method, url: &str) -> Result<(i32, i32), (u16, Value)> {
+    let not_found = || {
+        (
+            404,
+            json!({ "error": format!("there is nothing at {}", url) }),
+        )
+    };
+    let path = url.split('?').next().unwrap_or(url);
+    let mut segments = path.trim_matches('/').split('/');
+    let (day, part) = match (
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+    ) {
+        (Some("solve"), Some(day), Some(part), None) => (day, part),
+        _ => return Err(not_found()),
+    };
+    if *method != Method::Post {
+        return Err((
+            405,
+            json!({ "error": "use POST with the input as the body" }),
+        ));
+    }
+    return match (day.parse(), part.parse()) {
+        (Ok(day), Ok(part)) => Ok((day, part)),
+        _ => Err(not_found()),
+    };
+}
+
+fn solve(day: i32, part: i32, body: Vec<u8>, limits: &Limits) -> (u16, Value) {
+    let Ok(data) = String::from_utf8(body) else {
+        return (400, json!({ "error": "the input is not valid UTF-8" }));
+    };
+    return match runner::run_limited(day, &[part], &data, limits) {
+        Ok(run) => (
+            200,
+            json!({
+                "day": day,
+                "part": part,
+                "answer": run.parts[0].answer,
+                "picture": run.parts[0].picture,
+                "timing": {
+                    "parse_us": run.parse_time.as_micros() as u64,
+                    "solve_us": run.parts[0].solve_time.as_micros() as u64,
+                },
+            }),
+        ),
+        Err(e @ (RunError::UnknownDay(_) | RunError::UnknownPart(_))) => (
+            404,
+            json!({ "day": day, "part": part, "error": e.to_string() }),
+        ),
+        Err(RunError::Solve(e @ SolveError::Cancelled(limit))) => {
+            let status = match limit {
+                Limit::Time => 504,
+                Limit::Memory => 503,
+            };
+            (
+                status,
+                json!({ "day": day, "part": part, "error": e.to_string() }),
+            )
+        }
+        Err(RunError::Solve(e)) => {
+            let line = match &e {
+                SolveError::Parse { line, .. } => Some(*line),
+                _ => None,
+            };
+            (
+                422,
+                json!({ "day": day, "part": part, "error": e.to_string(), "line": line }),
+            )
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::{route, solve};
+    use crate::common::limits::Limits;
+    use serde_json::json;
+    use std::time::Duration;
+    use tiny_http::Method;
+
+    #[test]
+    fn routes() {
+        assert_eq!(route(&Method::Post, "/solve/7/2"), Ok((7, 2)));
+        assert_eq!(route(&Method::Get, "/solve/7/2").unwrap_err().0, 405);
+        assert_eq!(route(&Method::Post, "/solve/7").unwrap_err().0, 404);
+        assert_eq!(route(&Method::Post, "/solve/x/1").unwrap_err().0, 404);
+    }
+
+    #[test]
+    fn answers_and_errors() {
+        let limits = Limits {
+            time: Some(Duration::from_secs(10)),
+            memory: None,
+        };
+        let (status, body) = solve(2, 1, b"A Y\nB X\nC Z".to_vec(), &limits);
+        assert_eq!((status, &body["answer"]), (200, &json!("15")));
+        let (status, body) = solve(2, 1, b"A Y\nB Q".to_vec(), &limits);
+        assert_eq!((status, &body["line"]), (422, &json!(2)));
+        assert_eq!(solve(13, 1, vec![], &limits).0, 404);
+    }
+
+    #[test]
+    fn pictures() {
+        let limits = Limits::default();
+        let data = include_bytes!("../../data/day10.txt").to_vec();
+        let (status, body) = solve(10, 2, data, &limits);
+        assert_eq!((status, &body["answer"]), (200, &json!("EHBZLRJR")));
+        let picture = body["picture"].as_str().unwrap();
+        assert_eq!(picture.lines().count(), 6);
+        assert!(picture.starts_with("####.#..#.###..####.#....###....##.###.."));
+        assert_eq!(
+            solve(2, 1, b"A Y".to_vec(), &limits).1["picture"],
+            json!(null)
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use crate::common::error::SolveError;
+use crate::common::limits;
+use std::io::BufRead;
+
+/// The lines of a streamed input together with their 0-based index, just like
+/// `data.lines().enumerate()` would hand them out for an input that fits into memory. Every line
+/// is read only after `limits::check`, so the loops over them stop once the run is cancelled.
+pub fn numbered_lines(
+    input: impl BufRead,
+) -> impl Iterator<Item = Result<(usize, String), SolveError>> {
+    return input.lines().enumerate().map(|(i, line)| {
+        limits::check()?;
+        return Ok((i, line?));
+    });
+}
@@ -0,0 +1,137 @@
+// Picking the best few of many, like the three elves carrying the most calories in day01. Only k
+// items are kept around at any time, in a heap with the smallest of them on top, so that every
+// other item only needs to be compared with that one.
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// An item that made it into the top k, with its key and its position among all items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranked<K, T> {
+    pub key: K,
+    pub index: usize,
+    pub item: T,
+}
+
+// the order of the heap, where among equal keys the earlier item ranks higher, so that ties go
+// to the first one seen
+#[derive(Debug, Clone)]
+struct Entry<K, T>(Ranked<K, T>);
+
+impl<K: Ord, T> Ord for Entry<K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self
+            .0
+            .key
+            .cmp(&other.0.key)
+            .then(other.0.index.cmp(&self.0.index));
+    }
+}
+
+impl<K: Ord, T> PartialOrd for Entry<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<K: Ord, T> PartialEq for Entry<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.cmp(other) == Ordering::Equal;
+    }
+}
+
+impl<K: Ord, T> Eq for Entry<K, T> {}
+
+/// Keeps the `k` items with the largest keys out of all items pushed into it; for when the items
+/// come in one by one, like while reading an input.
+#[derive(Debug, Clone)]
+pub struct TopK<K, T> {
+    k: usize,
+    heap: BinaryHeap<Reverse<Entry<K, T>>>,
+    pushed: usize,
+}
+
+impl<K: Ord, T> TopK<K, T> {
+    pub fn new(k: usize) -> Self {
+        return TopK {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+            pushed: 0,
+        };
+    }
+
+    pub fn push(&mut self, key: K, item: T) {
+        let entry = Entry(Ranked {
+            key,
+            index: self.pushed,
+            item,
+        });
+        self.pushed += 1;
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(entry));
+        } else if self
+            .heap
+            .peek()
+            .is_some_and(|Reverse(smallest)| entry > *smallest)
+        {
+            self.heap.pop();
+            self.heap.push(Reverse(entry));
+        }
+    }
+
+    /// The items kept, largest key first; fewer than `k` if fewer were pushed.
+    pub fn into_sorted_vec(self) -> Vec<Ranked<K, T>> {
+        // ascending in reverse is descending
+        return self
+            .heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(entry)| entry.0)
+            .collect();
+    }
+}
+
+/// The `k` items with the largest keys, largest first.
+pub fn top_k_by_key<T, K: Ord>(
+    items: impl IntoIterator<Item = T>,
+    k: usize,
+    mut key: impl FnMut(&T) -> K,
+) -> Vec<Ranked<K, T>> {
+    let mut top = TopK::new(k);
+    for item in items {
+        top.push(key(&item), item);
+    }
+    return top.into_sorted_vec();
+}
+
+/// The `k` largest values, largest first.
+pub fn top_k<K: Ord>(values: impl IntoIterator<Item = K>, k: usize) -> Vec<K> {
+    let mut top = TopK::new(k);
+    for value in values {
+        top.push(value, ());
+    }
+    return top
+        .into_sorted_vec()
+        .into_iter()
+        .map(|ranked| ranked.key)
+        .collect();
+}
+
+#[cfg(test)]
+mod test {
+    use super::{top_k, top_k_by_key};
+
+    #[test]
+    fn best_few() {
+        assert_eq!(top_k([3, -1, 7, 7, 2], 3), vec![7, 7, 3]);
+        assert_eq!(top_k([-5, -2], 3), vec![-2, -5]);
+        assert_eq!(top_k([1, 2], 0), Vec::<i32>::new());
+
+        // ties go to the earlier word
+        let words = ["elf", "monkey", "rope", "crate", "tree"];
+        let top: Vec<(usize, &str)> = top_k_by_key(words, 3, |word| word.len())
+            .into_iter()
+            .map(|ranked| (ranked.index, ranked.item))
+            .collect();
+        assert_eq!(top, vec![(1, "monkey"), (3, "crate"), (2, "rope")]);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::common::error::SolveError;
+use crate::common::runner::{self, PartResult, RunError, DAYS};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// how many runs the timing pane keeps around
+const HISTORY_LENGTH: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Input,
+    Example,
+}
+
+impl Source {
+    fn name(&self) -> &'static str {
+        return match self {
+            Source::Input => "input",
+            Source::Example => "example",
+        };
+    }
+}
+
+struct Timing {
+    day: i32,
+    part: i32,
+    source: Source,
+    parse_time: Duration,
+    solve_time: Duration,
+}
+
+struct Answers {
+    day: i32,
+    source: Source,
+    parts: Vec<PartResult>,
+}
+
+struct Failure {
+    day: i32,
+    message: String,
+    // the offending input line for parse errors, with its line number
+    context: Option<(usize, String)>,
+}
+
+struct App {
+    data_dir: PathBuf,
+    days: ListState,
+    source: Source,
+    answers: Option<Answers>,
+    history: Vec<Timing>,
+    error: Option<Failure>,
+}
+
+/// Runs the full-screen terminal UI until the user quits. Inputs are read from
+/// `data_dir/dayNN.txt` and examples from `data_dir/examples/dayNN.txt`.
+pub fn run(data_dir: PathBuf) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new(data_dir).event_loop(&mut terminal);
+    ratatui::restore();
+    return result;
+}
+
+impl App {
+    fn new(data_dir: PathBuf) -> Self {
+        return App {
+            data_dir,
+            days: ListState::default().with_selected(Some(0)),
+            source: Source::Input,
+            answers: None,
+            history: vec![],
+            error: None,
+        };
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let selected = self.days.selected().unwrap_or(0);
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.days.select(Some((selected + 1) % DAYS.len()))
+                }
+                KeyCode::Up | KeyCode::Char('k') => self
+                    .days
+                    .select(Some((selected + DAYS.len() - 1) % DAYS.len())),
+                KeyCode::Char('e') => {
+                    self.source = match self.source {
+                        Source::Input => Source::Example,
+                        Source::Example => Source::Input,
+                    }
+                }
+                KeyCode::Char('1') => self.solve(&[1]),
+                KeyCode::Char('2') => self.solve(&[2]),
+                KeyCode::Char('b') | KeyCode::Enter => self.solve(&[1, 2]),
+                _ => {}
+            }
+        }
+    }
+
+    fn input_path(&self, day: i32) -> PathBuf {
+        let file = format!("day{:02}.txt", day);
+        return match self.source {
+            Source::Input => self.data_dir.join(file),
+            Source::Example => self.data_dir.join("examples").join(file),
+        };
+    }
+
+    fn solve(&mut self, parts: &[i32]) {
+        let day = DAYS[self.days.selected().unwrap_or(0)].day;
+        let path = self.input_path(day);
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                self.error = Some(Failure {
+                    day,
+                    message: format!("could not read {}: {}", path.display(), e),
+                    context: None,
+                });
+                return;
+            }
+        };
+        match runner::run(day, parts, &data) {
+            Ok(run) => {
+                for result in run.parts.iter() {
+                    self.history.insert(
+                        0,
+                        Timing {
+                            day,
+                            part: result.part,
+                            source: self.source,
+                            parse_time: run.parse_time,
+                            solve_time: result.solve_time,
+                        },
+                    );
+                }
+                self.history.truncate(HISTORY_LENGTH);
+                self.answers = Some(Answers {
+                    day,
+                    source: self.source,
+                    parts: run.parts,
+                });
+                self.error = None;
+            }
+            Err(e) => {
+                let context = match &e {
+                    RunError::Solve(SolveError::Parse { line, .. }) => data
+                        .lines()
+                        .nth(line - 1)
+                        .map(|text| (*line, text.to_string())),
+                    _ => None,
+                };
+                self.error = Some(Failure {
+                    day,
+                    message: e.to_string(),
+                    context,
+                });
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [days, right] =
+            Layout::horizontal([Constraint::Length(38), Constraint::Min(0)]).areas(main);
+        let [answers, history, error] = Layout::vertical([
+            Constraint::Min(10),
+            Constraint::Percentage(35),
+            Constraint::Length(5),
+        ])
+        .areas(right);
+
+        self.draw_days(frame, days);
+        self.draw_answers(frame, answers);
+        self.draw_history(frame, history);
+        self.draw_error(frame, error);
+        frame.render_widget(
+            Line::from(format!(
+                " j/k: select day   1/2: run part   b: run both   e: switch to {}   q: quit",
+                match self.source {
+                    Source::Input => "example",
+                    Source::Example => "input",
+                }
+            ))
+            .style(Style::new().add_modifier(Modifier::DIM)),
+            help,
+        );
+    }
+
+    fn draw_days(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = DAYS
+            .iter()
+            .map(|day| {
+                let stars = "*".repeat(day.stars as usize) + &" ".repeat(2 - day.stars as usize);
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:2} ", day.day)),
+                    Span::styled(stars, Style::new().fg(Color::Yellow)),
+                    Span::raw(format!(" {}", day.title)),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" days ({}) ", self.source.name())))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.days);
+    }
+
+    fn draw_answers(&self, frame: &mut Frame, area: Rect) {
+        let mut text = Text::default();
+        let mut title = " answers ".to_string();
+        if let Some(answers) = &self.answers {
+            title = format!(
+                " answers for day {} ({}) ",
+                answers.day,
+                answers.source.name()
+            );
+            for result in answers.parts.iter() {
+                let Some(picture) = &result.picture else {
+                    text.push_line(format!("part {}: {}", result.part, result.answer));
+                    continue;
+                };
+                // pictures, like day10's CRT, go below the answer, unless the answer is the picture
+                // itself; lit pixels get a full block so the letters are actually readable
+                match result.answer.contains('\n') {
+                    true => text.push_line(format!("part {}:", result.part)),
+                    false => text.push_line(format!("part {}: {}", result.part, result.answer)),
+                }
+                for row in picture.lines() {
+                    text.push_line(row.replace('#', "\u{2588}").replace('.', " "));
+                }
+            }
+        }
+        frame.render_widget(
+            Paragraph::new(text).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+
+    fn draw_history(&self, frame: &mut Frame, area: Rect) {
+        let rows = self.history.iter().map(|timing| {
+            Row::new(vec![
+                timing.day.to_string(),
+                timing.part.to_string(),
+                timing.source.name().to_string(),
+                format!("{:?}", timing.parse_time),
+                format!("{:?}", timing.solve_time),
+            ])
+        });
+        let widths = [
+            Constraint::Length(4),
+            Constraint::Length(5),
+            Constraint::Length(8),
+            Constraint::Length(14),
+            Constraint::Length(14),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec!["day", "part", "source", "parse", "solve"])
+                    .style(Style::new().add_modifier(Modifier::BOLD)),
+            )
+            .block(Block::bordered().title(" timings "));
+        frame.render_widget(table, area);
+    }
+
+    fn draw_error(&self, frame: &mut Frame, area: Rect) {
+        let mut text = Text::default();
+        let mut title = " last error ".to_string();
+        if let Some(failure) = &self.error {
+            title = format!(" last error (day {}) ", failure.day);
+            text.push_line(Line::styled(
+                failure.message.clone(),
+                Style::new().fg(Color::Red),
+            ));
+            if let Some((line, content)) = &failure.context {
+                text.push_line(format!("{:>5} | {}", line, content));
+            }
+        }
+        frame.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(title)),
+            area,
+        );
+    }
+}
@@ -0,0 +1,14 @@
+// WebAssembly entry point; build it with
+//   cargo rustc --lib --release --crate-type cdylib --target wasm32-unknown-unknown \
+//       --no-default-features --features wasm
+//   wasm-bindgen --target web --out-dir web/pkg target/wasm32-unknown-unknown/release/aoc2022.wasm
+// and serve web/ to try it out.
+use crate::common::runner;
+use wasm_bindgen::prelude::*;
+
+/// Solves one part of a day; errors are thrown as strings.
+#[wasm_bindgen]
+pub fn solve(day: i32, part: i32, input: &str) -> Result<String, String> {
+    let mut run = runner::run(day, &[part], input).map_err(|e| e.to_string())?;
+    return Ok(run.parts.remove(0).answer);
+}
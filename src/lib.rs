@@ -0,0 +1,9 @@
+#![cfg_attr(
+    feature = "nightly",
+    feature(int_roundings, iter_next_chunk, slice_partition_dedup)
+)]
+// explicit returns and index based loops are just how this code base is written
+#![allow(clippy::needless_return, clippy::needless_range_loop)]
+
+pub mod common;
+pub mod solutions;
@@ -1,31 +1,314 @@
-#![feature(int_roundings)]
-#![feature(iter_next_chunk)]
-#![feature(slice_partition_dedup)]
-
-mod common;
-mod solutions;
-
-use clap::Parser;
+use aoc2022::common;
+#[cfg(not(feature = "wasm"))]
+use aoc2022::common::image::Format;
+#[cfg(any(feature = "serve", not(feature = "wasm")))]
+use aoc2022::common::limits::Limits;
+#[cfg(not(feature = "wasm"))]
+use aoc2022::common::params;
+use aoc2022::common::runner::{RunError, DAYS};
+use clap::{Args, Parser, Subcommand};
 use std::error::Error;
+#[cfg(not(feature = "wasm"))]
+use std::path::Path;
+#[cfg(any(feature = "tui", not(feature = "wasm")))]
+use std::path::PathBuf;
+#[cfg(any(feature = "serve", not(feature = "wasm")))]
+use std::time::Duration;
+
+// counts allocations per thread, which is what memory limits are enforced with
+#[global_allocator]
+static ALLOC: common::alloc::Counting = common::alloc::Counting;
 
 /// Run the Advent of Code 2021 challenges
 #[derive(Parser)]
 #[clap(version = "0.0.1", author = "Author: github.com/tbreslein")]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Opts {
     /// which day of the AoC you wanna run; valid values: {1, 2, ..}
+    #[clap(short, long, required = true)]
+    day: Option<i32>,
+
+    /// which part of that day you wanna run; valid values: {1, 2}; runs both parts if left out
     #[clap(short, long)]
-    day: i32,
+    part: Option<i32>,
 
-    /// which part of that day you wanna run; valid values: {1, 2}
+    /// print how long parsing the input and solving each part took
     #[clap(short, long)]
-    part: i32,
+    timings: bool,
 
     /// file containing the data relevant to the challenge
-    file: String,
+    #[clap(required = true)]
+    file: Option<String>,
+
+    #[clap(flatten)]
+    limits: LimitArgs,
+
+    /// override one of the day's puzzle constants, e.g. `--param rounds_p2=500`; see `params`
+    #[clap(long = "param", value_name = "KEY=VALUE")]
+    params: Vec<String>,
+
+    /// also draw the day's state into this .pbm, .pgm or .ppm file; days 8, 10 and 12 can do that
+    #[cfg(not(feature = "wasm"))]
+    #[clap(long, value_name = "PATH")]
+    image: Option<PathBuf>,
+
+    /// draw every cell into the `--image` as a square this many pixels wide
+    #[cfg(not(feature = "wasm"))]
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    scale: u64,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Args)]
+struct LimitArgs {
+    /// stop a run after this many seconds and report it as timed out
+    #[clap(long)]
+    timeout: Option<f64>,
+
+    /// stop a run once it has allocated more than this many MiB
+    #[clap(long)]
+    memory_limit: Option<usize>,
+}
+
+// only the batch and serve commands and plain runs put limits on solvers
+#[cfg(any(feature = "serve", not(feature = "wasm")))]
+impl LimitArgs {
+    fn limits(&self) -> Limits {
+        Limits {
+            time: self.timeout.map(Duration::from_secs_f64),
+            memory: self.memory_limit.map(|mib| mib * 1024 * 1024),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// run both parts of a day on a bunch of inputs and compare the answers in a table
+    #[cfg(not(feature = "wasm"))]
+    Batch {
+        /// which day of the AoC you wanna run
+        #[clap(short, long)]
+        day: i32,
+
+        /// input files, directories containing them, or glob patterns like "inputs/day07-*.txt"
+        #[clap(required = true)]
+        inputs: Vec<String>,
+
+        #[clap(flatten)]
+        limits: LimitArgs,
+
+        /// override one of the day's puzzle constants, e.g. `--param rounds_p2=500`; see `params`
+        #[clap(long = "param", value_name = "KEY=VALUE")]
+        params: Vec<String>,
+    },
+
+    /// run the days on their inputs and write a Markdown or HTML report of answers and timings
+    #[cfg(not(feature = "wasm"))]
+    Report {
+        /// which days to run, e.g. "1,2,10"; runs all of them if left out
+        #[clap(short, long, value_delimiter = ',')]
+        days: Vec<i32>,
+
+        /// directory containing the dayNN.txt inputs
+        #[clap(long, default_value = "data")]
+        data_dir: PathBuf,
+
+        /// markdown or html
+        #[clap(short, long, default_value = "markdown")]
+        format: common::report::Format,
+
+        /// write the report to this file instead of printing it
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// list the puzzle constants that can be overridden with `--param`, with their defaults
+    Params {
+        /// only list the constants of this day
+        #[clap(short, long)]
+        day: Option<i32>,
+    },
+
+    /// browse the days, run them and compare timings in an interactive terminal UI
+    #[cfg(feature = "tui")]
+    Tui {
+        /// directory containing the dayNN.txt inputs and the examples/ directory
+        #[clap(long, default_value = "data")]
+        data_dir: PathBuf,
+    },
+
+    /// answer `POST /solve/{day}/{part}` requests, with the input as the body, in JSON
+    #[cfg(feature = "serve")]
+    Serve {
+        /// address to listen on
+        #[clap(long, default_value = "127.0.0.1:8022")]
+        address: String,
+
+        /// largest accepted input, in bytes
+        #[clap(long, default_value_t = 16 * 1024 * 1024)]
+        max_body: usize,
+
+        /// seconds a single solve may take before the request is answered with a timeout
+        #[clap(long, default_value_t = 10.0)]
+        timeout: f64,
+
+        /// MiB a single solve may allocate before the request is turned down
+        #[clap(long)]
+        memory_limit: Option<usize>,
+    },
+}
+
+// checks the `--param` overrides against the day's parameters and installs them for this thread
+#[cfg(not(feature = "wasm"))]
+fn install_params(day: i32, assignments: &[String]) -> Result<(), Box<dyn Error>> {
+    if assignments.is_empty() {
+        return Ok(());
+    }
+    let Some(day) = DAYS.iter().find(|d| d.day == day) else {
+        return Err(RunError::UnknownDay(day).into());
+    };
+    let overrides =
+        params::parse(day.params, assignments).map_err(|e| format!("--param: {}", e))?;
+    params::install(overrides);
+    Ok(())
+}
+
+fn list_params(day: Option<i32>) -> Result<(), Box<dyn Error>> {
+    let days: Vec<_> = DAYS
+        .iter()
+        .filter(|d| day.is_none_or(|day| d.day == day))
+        .collect();
+    if days.is_empty() {
+        return Err(RunError::UnknownDay(day.unwrap()).into());
+    }
+    for d in days {
+        if d.params.is_empty() {
+            if day.is_some() {
+                println!("day {} has no parameters", d.day);
+            }
+            continue;
+        }
+        println!("day {}: {}", d.day, d.title);
+        let values: Vec<String> = d
+            .params
+            .iter()
+            .map(|param| format!("{}: {} = {}", param.name, param.kind, param.default))
+            .collect();
+        let width = values.iter().map(|value| value.len()).max().unwrap_or(0);
+        for (value, param) in values.iter().zip(d.params) {
+            println!("    {:width$}  {}", value, param.help, width = width);
+        }
+    }
+    Ok(())
+}
+
+// draws what `day` makes of the input in `file` into `path`, each cell `scale` pixels wide
+#[cfg(not(feature = "wasm"))]
+fn write_image(
+    day: i32,
+    file: &str,
+    path: &Path,
+    format: Format,
+    scale: u64,
+) -> Result<(), Box<dyn Error>> {
+    let data = std::fs::read_to_string(file)?;
+    let Some(image) = common::runner::render(day, &data)? else {
+        return Err(format!("--image: day {} has nothing to draw", day).into());
+    };
+    std::fs::write(path, image.scaled(scale as usize).encode(format))?;
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let opts: Opts = Opts::parse();
-    common::pick_challenge::pick_challenge(opts.day, opts.part, opts.file)?;
+    match opts.command {
+        #[cfg(not(feature = "wasm"))]
+        Some(Command::Batch {
+            day,
+            inputs,
+            limits,
+            params,
+        }) => {
+            if !DAYS.iter().any(|d| d.day == day) {
+                return Err(RunError::UnknownDay(day).into());
+            }
+            install_params(day, &params)?;
+            let files = common::batch::collect_inputs(&inputs)?;
+            let entries = common::batch::run_batch(day, &files, &limits.limits());
+            print!("{}", common::batch::render_table(&entries));
+            let failed = entries.iter().filter(|entry| entry.result.is_err()).count();
+            if failed > 0 {
+                return Err(format!("{} of {} inputs failed", failed, entries.len()).into());
+            }
+        }
+        #[cfg(not(feature = "wasm"))]
+        Some(Command::Report {
+            days,
+            data_dir,
+            format,
+            output,
+        }) => {
+            if let Some(day) = days.iter().find(|day| !DAYS.iter().any(|d| d.day == **day)) {
+                return Err(RunError::UnknownDay(*day).into());
+            }
+            let reports = common::report::run_report(&days, &data_dir);
+            let page = common::report::render(&reports, format);
+            match output {
+                Some(output) => std::fs::write(output, page)?,
+                None => print!("{}", page),
+            }
+        }
+        Some(Command::Params { day }) => list_params(day)?,
+        #[cfg(feature = "tui")]
+        Some(Command::Tui { data_dir }) => common::tui::run(data_dir)?,
+        #[cfg(feature = "serve")]
+        Some(Command::Serve {
+            address,
+            max_body,
+            timeout,
+            memory_limit,
+        }) => common::serve::serve(common::serve::Config {
+            address,
+            max_body,
+            limits: LimitArgs {
+                timeout: Some(timeout),
+                memory_limit,
+            }
+            .limits(),
+        })?,
+        #[cfg(feature = "wasm")]
+        None => {
+            return Err("the wasm feature leaves out running challenges from input files".into())
+        }
+        #[cfg(not(feature = "wasm"))]
+        None => {
+            let parts = match opts.part {
+                Some(part) => vec![part],
+                None => vec![1, 2],
+            };
+            let (day, file) = (opts.day.unwrap(), opts.file.unwrap());
+            // a path the image cannot be written to is better told before solving than after
+            let image = match opts.image {
+                Some(path) => match Format::from_path(&path) {
+                    Some(format) => Some((path, format)),
+                    None => return Err("--image: expected a .pbm, .pgm or .ppm file".into()),
+                },
+                None => None,
+            };
+            install_params(day, &opts.params)?;
+            common::pick_challenge::pick_challenge(
+                day,
+                &parts,
+                file.clone(),
+                opts.timings,
+                &opts.limits.limits(),
+            )?;
+            if let Some((path, format)) = image {
+                write_image(day, &file, &path, format, opts.scale)?;
+            }
+        }
+    }
     Ok(())
 }
@@ -1,24 +1,210 @@
-pub fn solve_p1(data: &str) -> i32 {
-    data.split("\n\n")
-        .map(|block| block.lines().map(|l| l.parse::<i32>().unwrap()).sum())
-        .max()
-        .unwrap()
-}
-
-pub fn solve_p2(data: &str) -> i32 {
-    let tuple = data
-        .split("\n\n")
-        .map(|block| block.lines().map(|l| l.parse::<i32>().unwrap()).sum())
-        .fold((0, 0, 0), |t, x| {
-            if x > t.0 {
-                (x, t.0, t.1)
-            } else if x > t.1 {
-                (t.0, x, t.1)
-            } else if x > t.2 {
-                (t.0, t.1, x)
+use crate::common::error::SolveError;
+use crate::common::parse::{self, Line};
+use crate::common::stream::numbered_lines;
+use crate::common::topk::{self, TopK};
+use std::io::BufRead;
+
+type Elves = Vec<Vec<i32>>;
+pub type Input = Elves;
+
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    return parse::Input::new(data)
+        .blocks()
+        .map(|elf| elf.lines().map(|line| line.parse()).collect())
+        .collect();
+}
+
+fn overflow() -> SolveError {
+    return SolveError::invalid(format!("the calories add up to more than {}", i32::MAX));
+}
+
+fn total(calories: impl IntoIterator<Item = i32>) -> Result<i32, SolveError> {
+    return calories
+        .into_iter()
+        .try_fold(0i32, |total, x| total.checked_add(x).ok_or_else(overflow));
+}
+
+fn totals(elves: &Elves) -> Result<Vec<i32>, SolveError> {
+    return elves.iter().map(|elf| total(elf.iter().copied())).collect();
+}
+
+// folds the total of every elf into `acc` while reading the input, without keeping the elves
+fn fold_totals<B>(
+    input: impl BufRead,
+    acc: B,
+    mut f: impl FnMut(B, i32) -> B,
+) -> Result<B, SolveError> {
+    let mut acc = acc;
+    let mut current = 0;
+    for line in numbered_lines(input) {
+        let (i, line) = line?;
+        if line.is_empty() {
+            acc = f(acc, current);
+            current = 0;
+            continue;
+        }
+        let calories = Line::new(i + 1, &line).parse::<i32>()?;
+        current = current.checked_add(calories).ok_or_else(overflow)?;
+    }
+    return Ok(f(acc, current));
+}
+
+pub fn solve_p1(elves: &Input) -> Result<i32, SolveError> {
+    return Ok(totals(elves)?.into_iter().max().unwrap());
+}
+
+pub fn solve_p2(elves: &Input) -> Result<i32, SolveError> {
+    return total(topk::top_k(totals(elves)?, 3));
+}
+
+pub fn solve_p1_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    return fold_totals(input, 0, |max, x| max.max(x));
+}
+
+pub fn solve_p2_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    let top = fold_totals(input, TopK::new(3), |mut top, x| {
+        top.push(x, ());
+        top
+    })?;
+    return total(top.into_sorted_vec().iter().map(|ranked| ranked.key));
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+    use super::solve_p1;
+    use super::solve_p2;
+    use super::{solve_p1_stream, solve_p2_stream};
+    use crate::common::differential::{self, Rng};
+    use crate::common::error::SolveError;
+    use proptest::prelude::*;
+
+    fn elf_totals(data: &str) -> Vec<i32> {
+        let mut totals = vec![];
+        let mut current = 0;
+        for line in data.lines() {
+            if line.is_empty() {
+                totals.push(current);
+                current = 0;
             } else {
-                t
+                current += line.parse::<i32>().unwrap();
             }
-        });
-    return tuple.0 + tuple.1 + tuple.2;
+        }
+        totals.push(current);
+        totals.sort();
+        totals.reverse();
+        return totals;
+    }
+
+    fn reference_p1(data: &str) -> i32 {
+        return elf_totals(data)[0];
+    }
+
+    fn reference_p2(data: &str) -> i32 {
+        return elf_totals(data).iter().take(3).sum();
+    }
+
+    fn generate(rng: &mut Rng) -> String {
+        let elves = rng.range(1, 20);
+        return (0..elves)
+            .map(|_| {
+                (0..rng.range(1, 8))
+                    .map(|_| rng.range(1, 20_000).to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+    }
+
+    #[test]
+    fn p1_test() {
+        let data = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000";
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(24000));
+    }
+
+    #[test]
+    fn p2_test() {
+        let data = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000";
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(45000));
+    }
+
+    #[test]
+    fn totals_too_large() {
+        let overflow = Err(SolveError::invalid(format!(
+            "the calories add up to more than {}",
+            i32::MAX
+        )));
+        let one_elf = "2000000000\n2000000000";
+        assert_eq!(parse(one_elf).and_then(|elves| solve_p1(&elves)), overflow);
+        assert_eq!(solve_p1_stream(one_elf.as_bytes()), overflow);
+        // every elf on its own is fine, only the top three together are too many
+        let two_elves = "2000000000\n\n2000000000";
+        assert_eq!(
+            parse(two_elves).and_then(|elves| solve_p1(&elves)),
+            Ok(2_000_000_000)
+        );
+        assert_eq!(
+            parse(two_elves).and_then(|elves| solve_p2(&elves)),
+            overflow
+        );
+        assert_eq!(solve_p2_stream(two_elves.as_bytes()), overflow);
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn top_three_bounded_by_top_one(
+            elves in prop::collection::vec(prop::collection::vec(1..100_000i32, 1..8), 1..20),
+        ) {
+            let data = elves
+                .iter()
+                .map(|elf| elf.iter().map(|c| c.to_string()).collect::<Vec<_>>().join("\n"))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            let top = solve_p1(&parse(&data).unwrap()).unwrap();
+            prop_assert_eq!(top, elves.iter().map(|elf| elf.iter().sum::<i32>()).max().unwrap());
+            prop_assert!(solve_p2(&parse(&data).unwrap()).unwrap() >= top);
+            prop_assert!(solve_p2(&parse(&data).unwrap()).unwrap() <= 3 * top);
+        }
+    }
+
+    #[test]
+    fn p1_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p1_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p1(&input)),
+        );
+    }
+
+    #[test]
+    fn p2_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p2_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p2(&input)),
+        );
+    }
 }
@@ -1,31 +1,212 @@
-pub fn solve_p1(data: &str) -> i32 {
-    data.lines()
-        .fold( 0, |acc, line| acc + match line {
-            "A X" => 4,
-            "A Y" => 8,
-            "A Z" => 3,
-            "B X" => 1,
-            "B Y" => 5,
-            "B Z" => 9,
-            "C X" => 7,
-            "C Y" => 2,
-            "C Z" => 6,
-            _ => 0,
-        })
-}
-
-pub fn solve_p2(data: &str) -> i32 {
-    data.lines()
-        .fold( 0, |acc, line| acc + match line {
-            "A X" => 3,
-            "A Y" => 4,
-            "A Z" => 8,
-            "B X" => 1,
-            "B Y" => 5,
-            "B Z" => 9,
-            "C X" => 2,
-            "C Y" => 6,
-            "C Z" => 7,
-            _ => 0,
-        })
+use crate::common::error::SolveError;
+use crate::common::parse::{self, Line};
+use crate::common::stream::numbered_lines;
+use std::io::BufRead;
+
+type Round = (char, char);
+pub type Input = Vec<Round>;
+
+fn parse_round(line: Line) -> Result<Round, SolveError> {
+    return match line.text.as_bytes() {
+        [l @ b'A'..=b'C', b' ', r @ b'X'..=b'Z'] => Ok((*l as char, *r as char)),
+        _ => Err(line.error(format!("expected e.g. \"A X\", got {:?}", line.text))),
+    };
+}
+
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    return parse::Input::new(data).lines().map(parse_round).collect();
+}
+
+fn score_p1(round: &Round) -> i32 {
+    return match round {
+        ('A', 'X') => 4,
+        ('A', 'Y') => 8,
+        ('A', 'Z') => 3,
+        ('B', 'X') => 1,
+        ('B', 'Y') => 5,
+        ('B', 'Z') => 9,
+        ('C', 'X') => 7,
+        ('C', 'Y') => 2,
+        ('C', 'Z') => 6,
+        _ => 0,
+    };
+}
+
+fn score_p2(round: &Round) -> i32 {
+    return match round {
+        ('A', 'X') => 3,
+        ('A', 'Y') => 4,
+        ('A', 'Z') => 8,
+        ('B', 'X') => 1,
+        ('B', 'Y') => 5,
+        ('B', 'Z') => 9,
+        ('C', 'X') => 2,
+        ('C', 'Y') => 6,
+        ('C', 'Z') => 7,
+        _ => 0,
+    };
+}
+
+fn score_stream(input: impl BufRead, score: fn(&Round) -> i32) -> Result<i32, SolveError> {
+    let mut total = 0;
+    for line in numbered_lines(input) {
+        let (i, line) = line?;
+        total += score(&parse_round(Line::new(i + 1, &line))?);
+    }
+    return Ok(total);
+}
+
+pub fn solve_p1(rounds: &Input) -> Result<i32, SolveError> {
+    return Ok(rounds.iter().map(score_p1).sum());
+}
+
+pub fn solve_p2(rounds: &Input) -> Result<i32, SolveError> {
+    return Ok(rounds.iter().map(score_p2).sum());
+}
+
+pub fn solve_p1_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    return score_stream(input, score_p1);
+}
+
+pub fn solve_p2_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    return score_stream(input, score_p2);
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+    use super::solve_p1;
+    use super::solve_p2;
+    use super::{solve_p1_stream, solve_p2_stream};
+    use crate::common::differential::{self, Rng};
+    use proptest::prelude::*;
+
+    // 0 = rock, 1 = paper, 2 = scissors; each shape beats the one before it
+    fn shape(c: &str) -> i32 {
+        return match c {
+            "A" | "X" => 0,
+            "B" | "Y" => 1,
+            "C" | "Z" => 2,
+            _ => panic!("unknown shape {}", c),
+        };
+    }
+
+    fn score(opponent: i32, me: i32) -> i32 {
+        let outcome = if opponent == me {
+            3
+        } else if (opponent + 1) % 3 == me {
+            6
+        } else {
+            0
+        };
+        return me + 1 + outcome;
+    }
+
+    fn reference_p1(data: &str) -> i32 {
+        return data
+            .lines()
+            .map(|line| {
+                let (opponent, me) = line.split_once(' ').unwrap();
+                score(shape(opponent), shape(me))
+            })
+            .sum();
+    }
+
+    fn reference_p2(data: &str) -> i32 {
+        return data
+            .lines()
+            .map(|line| {
+                let (opponent, outcome) = line.split_once(' ').unwrap();
+                let opponent = shape(opponent);
+                let me = match outcome {
+                    "X" => (opponent + 2) % 3,
+                    "Y" => opponent,
+                    _ => (opponent + 1) % 3,
+                };
+                score(opponent, me)
+            })
+            .sum();
+    }
+
+    fn generate(rng: &mut Rng) -> String {
+        return (0..rng.range(1, 50))
+            .map(|_| {
+                format!(
+                    "{} {}",
+                    rng.pick(&["A", "B", "C"]),
+                    rng.pick(&["X", "Y", "Z"])
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    #[test]
+    fn p1_test() {
+        assert_eq!(solve_p1(&parse("A Y\nB X\nC Z").unwrap()), Ok(15));
+    }
+
+    #[test]
+    fn p2_test() {
+        assert_eq!(solve_p2(&parse("A Y\nB X\nC Z").unwrap()), Ok(12));
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    proptest! {
+        // every round is worth between 1 (lost with rock) and 9 (won with scissors)
+        #[test]
+        fn scores_per_round_bounded(
+            rounds in prop::collection::vec((0..3usize, 0..3usize), 1..50),
+        ) {
+            let data = rounds
+                .iter()
+                .map(|(o, m)| format!("{} {}", ["A", "B", "C"][*o], ["X", "Y", "Z"][*m]))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let n = rounds.len() as i32;
+            let input = parse(&data).unwrap();
+            for score in [solve_p1(&input).unwrap(), solve_p2(&input).unwrap()] {
+                prop_assert!(score >= n && score <= 9 * n);
+            }
+        }
+    }
+
+    #[test]
+    fn p1_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p1_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p1(&input)),
+        );
+    }
+
+    #[test]
+    fn p2_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p2_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p2(&input)),
+        );
+    }
 }
@@ -1,6 +1,12 @@
+use crate::common::error::SolveError;
+use crate::common::parse::{self, Line};
+use crate::common::stream::numbered_lines;
 use std::collections::HashMap;
+use std::io::BufRead;
 use std::iter::zip;
 
+pub type Input<'a> = Vec<&'a str>;
+
 fn get_prios() -> HashMap<char, i32> {
     let mut prio = HashMap::<char, i32>::new();
     for (c, i) in zip(('a'..='z').chain('A'..='Z'), 1..) {
@@ -9,43 +15,266 @@ fn get_prios() -> HashMap<char, i32> {
     return prio;
 }
 
-pub fn solve_p1(data: &str) -> i32 {
+fn check_rucksack(line: Line<'_>) -> Result<&str, SolveError> {
+    if !line.text.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(line.error(format!("not a rucksack: {:?}", line.text)));
+    }
+    if !line.text.len().is_multiple_of(2) {
+        return Err(line.error("rucksack has an odd number of items"));
+    }
+    return Ok(line.text);
+}
+
+pub fn parse(data: &str) -> Result<Input<'_>, SolveError> {
+    return parse::Input::new(data)
+        .lines()
+        .map(check_rucksack)
+        .collect();
+}
+
+fn misplaced_item(prio: &HashMap<char, i32>, line: &str) -> i32 {
+    let (l, r) = line.split_at((0.5 * line.len() as f64) as usize);
+    for c in l.chars() {
+        if r.contains(c) {
+            return *prio.get(&c).unwrap();
+        }
+    }
+    return 0;
+}
+
+fn badge<S: AsRef<str>>(prio: &HashMap<char, i32>, chunk: &[S; 3]) -> i32 {
+    for c in chunk[0].as_ref().chars() {
+        if chunk[1].as_ref().contains(c) && chunk[2].as_ref().contains(c) {
+            return *prio.get(&c).unwrap();
+        }
+    }
+    return 0;
+}
+
+pub fn solve_p1(lines: &Input) -> Result<i32, SolveError> {
     let prio = get_prios();
-    data.lines()
-        .map(|line| line.split_at((0.5 * line.len() as f64) as usize))
-        .map(|(l, r)| {
-            for c in l.chars() {
-                if r.contains(c) {
-                    return *prio.get(&c).unwrap();
-                }
-            }
-            return 0;
-        })
-        .sum()
+    return Ok(lines.iter().map(|line| misplaced_item(&prio, line)).sum());
 }
 
-pub fn solve_p2(data: &str) -> i32 {
+pub fn solve_p2(lines: &Input) -> Result<i32, SolveError> {
     let prio = get_prios();
     let mut tally = 0;
-    let mut lines = data.lines().into_iter();
-    while let Ok(chunk) = lines.next_chunk::<3>() {
-        for c in chunk[0].chars() {
-            if chunk[1].contains(c) && chunk[2].contains(c) {
-                tally += *prio.get(&c).unwrap();
-                break;
-            }
+    #[cfg(feature = "nightly")]
+    {
+        let mut lines = lines.iter().copied();
+        while let Ok(chunk) = lines.next_chunk::<3>() {
+            tally += badge(&prio, &chunk);
         }
     }
-    return tally;
+    #[cfg(not(feature = "nightly"))]
+    for chunk in lines.chunks_exact(3) {
+        tally += badge(&prio, chunk.try_into().unwrap());
+    }
+    return Ok(tally);
+}
+
+pub fn solve_p1_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    let prio = get_prios();
+    let mut tally = 0;
+    for line in numbered_lines(input) {
+        let (i, line) = line?;
+        tally += misplaced_item(&prio, check_rucksack(Line::new(i + 1, &line))?);
+    }
+    return Ok(tally);
+}
+
+pub fn solve_p2_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    let prio = get_prios();
+    let mut tally = 0;
+    let mut chunk: [String; 3] = Default::default();
+    for line in numbered_lines(input) {
+        let (i, line) = line?;
+        check_rucksack(Line::new(i + 1, &line))?;
+        chunk[i % 3] = line;
+        if i % 3 == 2 {
+            tally += badge(&prio, &chunk);
+        }
+    }
+    return Ok(tally);
 }
 
 #[cfg(test)]
 mod test {
+    use super::parse;
+    use super::solve_p1;
     use super::solve_p2;
+    use super::{solve_p1_stream, solve_p2_stream};
+    use crate::common::differential::{self, Rng};
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+
+    const ITEMS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    fn priority(c: char) -> i32 {
+        return ITEMS.find(c).unwrap() as i32 + 1;
+    }
+
+    fn common_item(sets: &[HashSet<char>]) -> char {
+        let common: Vec<&char> = sets[0]
+            .iter()
+            .filter(|c| sets.iter().all(|set| set.contains(c)))
+            .collect();
+        assert_eq!(common.len(), 1);
+        return *common[0];
+    }
+
+    fn reference_p1(data: &str) -> i32 {
+        return data
+            .lines()
+            .map(|line| {
+                let (l, r) = line.split_at(line.len() / 2);
+                priority(common_item(&[l.chars().collect(), r.chars().collect()]))
+            })
+            .sum();
+    }
+
+    fn reference_p2(data: &str) -> i32 {
+        let lines: Vec<HashSet<char>> = data.lines().map(|line| line.chars().collect()).collect();
+        return lines
+            .chunks(3)
+            .map(|group| priority(common_item(group)))
+            .sum();
+    }
+
+    // every rucksack has exactly one item type in both compartments
+    fn generate_p1(rng: &mut Rng) -> String {
+        return (0..rng.range(1, 30))
+            .map(|_| {
+                let mut items: Vec<char> = ITEMS.chars().collect();
+                rng.shuffle(&mut items);
+                let (shared, pools) = items.split_first().unwrap();
+                let (left_pool, right_pool) = pools.split_at(pools.len() / 2);
+                let half = rng.range(1, 16);
+                let mut left = vec![*shared];
+                let mut right = vec![*shared];
+                for _ in 1..half {
+                    left.push(*rng.pick(left_pool));
+                    right.push(*rng.pick(right_pool));
+                }
+                rng.shuffle(&mut left);
+                rng.shuffle(&mut right);
+                left.into_iter().chain(right).collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    // every group of three rucksacks has exactly one item type in common
+    fn generate_p2(rng: &mut Rng) -> String {
+        let items: Vec<char> = ITEMS.chars().collect();
+        let mut lines = vec![];
+        for _ in 0..rng.range(1, 10) {
+            let badge = *rng.pick(&items);
+            let others: Vec<char> = items.iter().filter(|c| **c != badge).copied().collect();
+            loop {
+                let group: Vec<Vec<char>> = (0..3)
+                    .map(|_| {
+                        let mut line = vec![badge];
+                        for _ in 1..2 * rng.range(1, 12) {
+                            line.push(*rng.pick(&others));
+                        }
+                        rng.shuffle(&mut line);
+                        line
+                    })
+                    .collect();
+                let sets: Vec<HashSet<char>> =
+                    group.iter().map(|l| l.iter().copied().collect()).collect();
+                if others
+                    .iter()
+                    .any(|c| sets.iter().all(|set| set.contains(c)))
+                {
+                    continue;
+                }
+                lines.extend(group.into_iter().map(|l| l.into_iter().collect::<String>()));
+                break;
+            }
+        }
+        return lines.join("\n");
+    }
+
+    #[test]
+    fn p1_test() {
+        let data = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\nwMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw\n";
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(157));
+    }
 
     #[test]
     fn p2_test() {
         let data = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\nwMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw\n";
-        assert_eq!(solve_p2(data), 70);
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(70));
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate_p1,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate_p2,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    proptest! {
+        // the left compartment only draws from the first half of the remaining items and the
+        // right one from the second half, so the planted item is the only one they share
+        #[test]
+        fn finds_planted_item(
+            rucksacks in prop::collection::vec(
+                (0..52usize, prop::collection::vec((0..25usize, 25..51usize), 0..15)),
+                1..20,
+            ),
+        ) {
+            let data = rucksacks
+                .iter()
+                .map(|(shared, pairs)| {
+                    let shared = ITEMS.chars().nth(*shared).unwrap();
+                    let others: Vec<char> = ITEMS.chars().filter(|c| *c != shared).collect();
+                    let left: String = pairs.iter().map(|(l, _)| others[*l]).collect();
+                    let right: String = pairs.iter().map(|(_, r)| others[*r]).collect();
+                    format!("{}{}{}{}", left, shared, shared, right)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let expected: i32 = rucksacks
+                .iter()
+                .map(|(shared, _)| *shared as i32 + 1)
+                .sum();
+            prop_assert_eq!(solve_p1(&parse(&data).unwrap()), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn p1_stream_matches() {
+        differential::check(
+            200,
+            generate_p1,
+            |d| solve_p1_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p1(&input)),
+        );
+    }
+
+    #[test]
+    fn p2_stream_matches() {
+        differential::check(
+            200,
+            generate_p2,
+            |d| solve_p2_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p2(&input)),
+        );
     }
 }
@@ -1,53 +1,129 @@
-pub fn solve_p1(data: &str) -> i32 {
-    return data
-        .lines()
-        .map(|line| {
-            let (l, r) = line.split_once(',').unwrap();
-            let (ll, lr) = l.split_once('-').unwrap();
-            let (rl, rr) = r.split_once('-').unwrap();
-            return (
-                ll.parse::<i32>().unwrap(),
-                lr.parse::<i32>().unwrap(),
-                rl.parse::<i32>().unwrap(),
-                rr.parse::<i32>().unwrap(),
-            );
-        })
-        .fold(0, |acc, (ll, lr, rl, rr)| {
-            if (ll >= rl && lr <= rr) || (rl >= ll && rr <= lr) {
-                acc + 1
-            } else {
-                acc
-            }
-        });
+use crate::common::error::SolveError;
+use crate::common::interval::Interval;
+use crate::common::parse::{self, Line, Scanner};
+use crate::common::stream::numbered_lines;
+use std::io::BufRead;
+
+type Pair = (Interval, Interval);
+pub type Input = Vec<Pair>;
+
+// e.g. "2-4"
+fn sections(line: &Line, scanner: &mut Scanner) -> Result<Interval, SolveError> {
+    let start = scanner.number()?;
+    scanner.literal("-")?;
+    let end = scanner.number()?;
+    return Interval::new(start, end)
+        .ok_or_else(|| line.error(format!("the sections {}-{} run backwards", start, end)));
+}
+
+// e.g. "2-4,6-8"
+fn parse_pair(line: Line) -> Result<Pair, SolveError> {
+    let mut scanner = line.scan();
+    let left = sections(&line, &mut scanner)?;
+    scanner.literal(",")?;
+    let right = sections(&line, &mut scanner)?;
+    scanner.end()?;
+    return Ok((left, right));
+}
+
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    return parse::Input::new(data).lines().map(parse_pair).collect();
+}
+
+fn fully_contains((left, right): &Pair) -> bool {
+    return left.contains_interval(right) || right.contains_interval(left);
+}
+
+fn overlaps((left, right): &Pair) -> bool {
+    return left.overlaps(right);
+}
+
+fn count_stream(input: impl BufRead, predicate: fn(&Pair) -> bool) -> Result<i32, SolveError> {
+    let mut count = 0;
+    for line in numbered_lines(input) {
+        let (i, line) = line?;
+        if predicate(&parse_pair(Line::new(i + 1, &line))?) {
+            count += 1;
+        }
+    }
+    return Ok(count);
+}
+
+pub fn solve_p1(pairs: &Input) -> Result<i32, SolveError> {
+    return Ok(pairs.iter().filter(|pair| fully_contains(pair)).count() as i32);
+}
+
+pub fn solve_p2(pairs: &Input) -> Result<i32, SolveError> {
+    return Ok(pairs.iter().filter(|pair| overlaps(pair)).count() as i32);
 }
 
-pub fn solve_p2(data: &str) -> i32 {
-    return data
-        .lines()
-        .map(|line| {
-            let (l, r) = line.split_once(',').unwrap();
-            let (ll, lr) = l.split_once('-').unwrap();
-            let (rl, rr) = r.split_once('-').unwrap();
-            return (
-                ll.parse::<i32>().unwrap(),
-                lr.parse::<i32>().unwrap(),
-                rl.parse::<i32>().unwrap(),
-                rr.parse::<i32>().unwrap(),
-            );
-        })
-        .fold(0, |acc, (ll, lr, rl, rr)| {
-            if (lr >= rl && ll <= rl) || (rr >= ll && rl <= ll) {
-                acc + 1
-            } else {
-                acc
-            }
-        });
+pub fn solve_p1_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    return count_stream(input, fully_contains);
+}
+
+pub fn solve_p2_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    return count_stream(input, overlaps);
 }
 
 #[cfg(test)]
 mod test {
+    use super::parse;
     use super::solve_p1;
     use super::solve_p2;
+    use super::{solve_p1_stream, solve_p2_stream};
+    use crate::common::differential::{self, Rng};
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+
+    // the two elves' section ranges, each end unordered
+    type RawPair = ((i32, i32), (i32, i32));
+
+    fn render(pairs: &[RawPair]) -> String {
+        return pairs
+            .iter()
+            .map(|((a, b), (c, d))| format!("{}-{},{}-{}", a.min(b), a.max(b), c.min(d), c.max(d)))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    fn assignments(data: &str) -> Vec<(HashSet<i32>, HashSet<i32>)> {
+        return data
+            .lines()
+            .map(|line| {
+                let sections: Vec<i32> =
+                    line.split([',', '-']).map(|x| x.parse().unwrap()).collect();
+                (
+                    (sections[0]..=sections[1]).collect(),
+                    (sections[2]..=sections[3]).collect(),
+                )
+            })
+            .collect();
+    }
+
+    fn reference_p1(data: &str) -> i32 {
+        return assignments(data)
+            .iter()
+            .filter(|(l, r)| l.is_subset(r) || r.is_subset(l))
+            .count() as i32;
+    }
+
+    fn reference_p2(data: &str) -> i32 {
+        return assignments(data)
+            .iter()
+            .filter(|(l, r)| !l.is_disjoint(r))
+            .count() as i32;
+    }
+
+    fn generate(rng: &mut Rng) -> String {
+        return (0..rng.range(1, 50))
+            .map(|_| {
+                let (a, b) = (rng.range(1, 20), rng.range(1, 20));
+                let (c, d) = (rng.range(1, 20), rng.range(1, 20));
+                format!("{}-{},{}-{}", a.min(b), a.max(b), c.min(d), c.max(d))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
 
     #[test]
     fn p1_test() {
@@ -57,7 +133,7 @@ mod test {
 2-8,3-7
 6-6,4-6
 2-6,4-8";
-        assert_eq!(solve_p1(&data), 2);
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(2));
     }
 
     #[test]
@@ -68,6 +144,62 @@ mod test {
 2-8,3-7
 6-6,4-6
 2-6,4-8";
-        assert_eq!(solve_p2(&data), 4);
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(4));
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    proptest! {
+        // full containment implies an overlap, and neither cares which elf comes first
+        #[test]
+        fn containment_implies_overlap(
+            pairs in prop::collection::vec(((1..30i32, 1..30i32), (1..30i32, 1..30i32)), 1..50),
+        ) {
+            let data = render(&pairs);
+            let swapped: Vec<_> = pairs.iter().map(|(l, r)| (*r, *l)).collect();
+            let swapped = render(&swapped);
+            let input = parse(&data).unwrap();
+            prop_assert!(solve_p1(&input).unwrap() <= solve_p2(&input).unwrap());
+            prop_assert_eq!(solve_p1(&parse(&data).unwrap()), solve_p1(&parse(&swapped).unwrap()));
+            prop_assert_eq!(solve_p2(&parse(&data).unwrap()), solve_p2(&parse(&swapped).unwrap()));
+        }
+    }
+
+    #[test]
+    fn p1_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p1_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p1(&input)),
+        );
+    }
+
+    #[test]
+    fn p2_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p2_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p2(&input)),
+        );
     }
 }
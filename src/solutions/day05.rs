@@ -1,5 +1,9 @@
-#[derive(Debug)]
-struct Step {
+use crate::common::error::SolveError;
+use crate::common::limits;
+use crate::common::parse::{self, Line, Scanner};
+
+#[derive(Debug, PartialEq)]
+pub struct Step {
     amount: usize,
     from: usize,
     to: usize,
@@ -7,22 +11,55 @@ struct Step {
 type Instructions = Vec<Step>;
 type Stack = Vec<char>;
 type Hold = Vec<Stack>;
+pub type Input = (Hold, Instructions);
 
-fn parse(data: &str) -> (Hold, Instructions) {
-    let (raw_hold, raw_instructions) = data.split_once("\n\n").unwrap();
-    let mut hold_iterator = raw_hold.lines().rev();
-    let length = hold_iterator
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .len();
+// e.g. "move 1 from 2 to 1", with the stacks numbered from 1
+fn parse_step(line: Line, stacks: usize) -> Result<Step, SolveError> {
+    let mut scanner = line.scan();
+    let stack = |scanner: &mut Scanner| -> Result<usize, SolveError> {
+        let n: usize = scanner.number()?;
+        if !(1..=stacks).contains(&n) {
+            return Err(line.error(format!("there is no stack {}", n)));
+        }
+        return Ok(n - 1);
+    };
+    scanner.literal("move ")?;
+    let amount = scanner.number()?;
+    scanner.literal(" from ")?;
+    let from = stack(&mut scanner)?;
+    scanner.literal(" to ")?;
+    let to = stack(&mut scanner)?;
+    scanner.end()?;
+    return Ok(Step { amount, from, to });
+}
+
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    let input = parse::Input::new(data);
+    let mut blocks = input.blocks();
+    let drawing = blocks.next().unwrap();
+    let moves = blocks.next().ok_or_else(|| {
+        SolveError::parse(drawing.end(), "missing the empty line after the drawing")
+    })?;
+    if blocks.next().is_some() {
+        return Err(SolveError::parse(
+            moves.end(),
+            "expected a move, got an empty line",
+        ));
+    }
+    let lines: Vec<Line> = drawing.lines().collect();
+    let Some((numbers, crates)) = lines.split_last() else {
+        return Err(SolveError::parse(1, "missing the stack numbers"));
+    };
+    let length = numbers.ints::<usize>()?.len();
+    if length == 0 {
+        return Err(numbers.error("missing the stack numbers"));
+    }
     let mut hold: Hold = vec![vec![]; length];
-    for line in hold_iterator {
-        let chars: Vec<char> = line.chars().collect();
+    for line in crates.iter().rev() {
+        let chars: Vec<char> = line.text.chars().collect();
         for i in 0..length {
             let pos = 1 + i * 4;
-            if chars.len() < pos {
+            if chars.len() <= pos {
                 continue;
             }
             if chars[pos].is_alphabetic() {
@@ -30,58 +67,192 @@ fn parse(data: &str) -> (Hold, Instructions) {
             }
         }
     }
-    let instructions = raw_instructions
+    let instructions = moves
         .lines()
-        .map(|line| {
-            let words: Vec<&str> = line.split_whitespace().collect();
-            return Step {
-                amount: words[1].parse::<usize>().unwrap(),
-                from: words[3].parse::<usize>().unwrap() - 1,
-                to: words[5].parse::<usize>().unwrap() - 1,
-            };
-        })
-        .collect();
-    return (hold, instructions);
+        .map(|line| parse_step(line, length))
+        .collect::<Result<_, _>>()?;
+    return Ok((hold, instructions));
 }
 
-pub fn solve_p1(data: &str) -> String {
-    let (mut hold, instructions) = parse(data);
-    for step in instructions {
-        for _ in 0..step.amount {
-            let c = hold[step.from].pop().unwrap();
-            hold[step.to].push(c);
-        }
+// the CrateMover 9000 moves one crate at a time
+fn crate_mover_9000(hold: &mut Hold, step: &Step) -> Result<(), SolveError> {
+    for _ in 0..step.amount {
+        let c = pop_crate(hold, step.from)?;
+        hold[step.to].push(c);
     }
+    return Ok(());
+}
+
+// the CrateMover 9001 moves all of them at once, so they keep their order
+fn crate_mover_9001(hold: &mut Hold, step: &Step) -> Result<(), SolveError> {
+    let mut cs = vec![];
+    for _ in 0..step.amount {
+        cs.push(pop_crate(hold, step.from)?);
+    }
+    cs.reverse();
+    for c in cs {
+        hold[step.to].push(c);
+    }
+    return Ok(());
+}
+
+fn pop_crate(hold: &mut Hold, stack: usize) -> Result<char, SolveError> {
+    return hold[stack]
+        .pop()
+        .ok_or_else(|| SolveError::invalid(format!("stack {} ran out of crates", stack + 1)));
+}
+
+// empty stacks simply do not contribute to the message
+fn top_crates(hold: Hold) -> String {
     let mut result: String = "".to_string();
     for mut stack in hold {
-        result.push(stack.pop().unwrap());
+        if let Some(c) = stack.pop() {
+            result.push(c);
+        }
     }
     return result;
 }
 
-pub fn solve_p2(data: &str) -> String {
-    let (mut hold, instructions) = parse(data);
+pub fn solve_p1((hold, instructions): &Input) -> Result<String, SolveError> {
+    let mut hold = hold.clone();
     for step in instructions {
-        let mut cs = vec![];
-        for _ in 0..step.amount {
-            cs.push(hold[step.from].pop().unwrap());
-        }
-        cs.reverse();
-        for c in cs {
-            hold[step.to].push(c);
-        }
+        limits::check()?;
+        crate_mover_9000(&mut hold, step)?;
     }
-    let mut result: String = "".to_string();
-    for mut stack in hold {
-        result.push(stack.pop().unwrap());
+    return Ok(top_crates(hold));
+}
+
+pub fn solve_p2((hold, instructions): &Input) -> Result<String, SolveError> {
+    let mut hold = hold.clone();
+    for step in instructions {
+        limits::check()?;
+        crate_mover_9001(&mut hold, step)?;
     }
-    return result;
+    return Ok(top_crates(hold));
 }
 
 #[cfg(test)]
 mod test {
     use super::solve_p1;
     use super::solve_p2;
+    use super::{crate_mover_9000, crate_mover_9001, parse, Hold, Step};
+    use crate::common::differential::{self, Rng};
+    use crate::common::error::SolveError;
+    use proptest::prelude::*;
+
+    // stacks, bottom to top, and the (amount, from, to) moves with 0-based stack indices
+    type Drawing = (Vec<Vec<char>>, Vec<(usize, usize, usize)>);
+
+    fn reference_parse(data: &str) -> Drawing {
+        let (drawing, moves) = data.split_once("\n\n").unwrap();
+        let rows: Vec<&str> = drawing.lines().collect();
+        let (labels, crates) = rows.split_last().unwrap();
+        // every stack sits in the same column as its label
+        let columns: Vec<usize> = labels
+            .char_indices()
+            .filter(|(_, c)| c.is_ascii_digit())
+            .map(|(i, _)| i)
+            .collect();
+        let mut stacks = vec![vec![]; columns.len()];
+        for row in crates.iter().rev() {
+            for (stack, column) in stacks.iter_mut().zip(columns.iter()) {
+                if let Some(c) = row.chars().nth(*column).filter(|c| c.is_alphabetic()) {
+                    stack.push(c);
+                }
+            }
+        }
+        let moves = moves
+            .lines()
+            .map(|line| {
+                let numbers: Vec<usize> = line
+                    .split(' ')
+                    .filter_map(|word| word.parse().ok())
+                    .collect();
+                (numbers[0], numbers[1] - 1, numbers[2] - 1)
+            })
+            .collect();
+        return (stacks, moves);
+    }
+
+    fn reference(data: &str, keep_order: bool) -> String {
+        let (mut stacks, moves) = reference_parse(data);
+        for (amount, from, to) in moves {
+            let split = stacks[from].len() - amount;
+            let mut moved = stacks[from].split_off(split);
+            if !keep_order {
+                moved.reverse();
+            }
+            stacks[to].extend(moved);
+        }
+        return stacks.iter().map(|stack| stack.last().unwrap()).collect();
+    }
+
+    fn reference_p1(data: &str) -> String {
+        return reference(data, false);
+    }
+
+    fn reference_p2(data: &str) -> String {
+        return reference(data, true);
+    }
+
+    fn render(stacks: &[Vec<char>], moves: &[(usize, usize, usize)]) -> String {
+        let tallest = stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+        let mut drawing = vec![];
+        for level in (0..tallest).rev() {
+            let row = stacks
+                .iter()
+                .map(|s| match s.get(level) {
+                    Some(c) => format!("[{}]", c),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            drawing.push(row.trim_end().to_string());
+        }
+        drawing.push(
+            (1..=stacks.len())
+                .map(|i| format!(" {} ", i))
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        let moves: Vec<String> = moves
+            .iter()
+            .map(|(amount, from, to)| format!("move {} from {} to {}", amount, from + 1, to + 1))
+            .collect();
+        return format!("{}\n\n{}", drawing.join("\n"), moves.join("\n"));
+    }
+
+    // only generates moves that are possible, and makes sure that no stack ends up empty
+    fn generate(rng: &mut Rng) -> String {
+        loop {
+            let n = rng.range(1, 9) as usize;
+            let stacks: Vec<Vec<char>> = (0..n)
+                .map(|_| {
+                    (0..rng.range(0, 6))
+                        .map(|_| (b'A' + rng.below(26) as u8) as char)
+                        .collect()
+                })
+                .collect();
+            let mut heights: Vec<usize> = stacks.iter().map(|s| s.len()).collect();
+            let mut moves = vec![];
+            for _ in 0..rng.range(0, 20) {
+                let from = rng.below(n);
+                let to = rng.below(n);
+                if from == to || heights[from] == 0 {
+                    continue;
+                }
+                let amount = rng.range(1, heights[from] as i64) as usize;
+                heights[from] -= amount;
+                heights[to] += amount;
+                moves.push((amount, from, to));
+            }
+            if heights.contains(&0) {
+                continue;
+            }
+
+            return render(&stacks, &moves);
+        }
+    }
 
     #[test]
     fn p1_test() {
@@ -94,7 +265,7 @@ move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
-        assert_eq!(solve_p1(&data), "CMZ");
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok("CMZ".to_string()));
     }
 
     #[test]
@@ -108,6 +279,100 @@ move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
-        assert_eq!(solve_p2(&data), "MCD");
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok("MCD".to_string()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        // the crate row is exactly as long as the position of the second stack
+        assert!(parse("[A]  \n 1   2 \n\nmove 1 from 1 to 2").is_ok());
+        assert_eq!(
+            parse("[A]\n 1 \n\nmove 1 from 1"),
+            Err(SolveError::parse(
+                4,
+                "expected \" to \" in column 14, got \"\""
+            ))
+        );
+        assert_eq!(
+            parse("[A]\n 1 \n\nmove 1 from 1 to 2"),
+            Err(SolveError::parse(4, "there is no stack 2"))
+        );
+        assert_eq!(
+            solve_p1(&parse("[A]\n 1   2 \n\nmove 2 from 1 to 2").unwrap()),
+            Err(SolveError::invalid("stack 1 ran out of crates"))
+        );
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    // stacks plus raw moves, which get turned into possible moves by tracking the stack heights
+    fn hold_and_moves() -> impl Strategy<Value = Drawing> {
+        let stacks = prop::collection::vec(
+            prop::collection::vec(prop::char::range('A', 'Z'), 0..6),
+            1..10,
+        );
+        let raw_moves = prop::collection::vec((1..10usize, 0..9usize, 0..9usize), 0..20);
+        return (stacks, raw_moves).prop_map(|(stacks, raw_moves)| {
+            let mut heights: Vec<usize> = stacks.iter().map(|s| s.len()).collect();
+            let mut moves = vec![];
+            for (amount, from, to) in raw_moves {
+                let (from, to) = (from % stacks.len(), to % stacks.len());
+                let amount = amount.min(heights[from]);
+                if from != to && amount > 0 {
+                    heights[from] -= amount;
+                    heights[to] += amount;
+                    moves.push((amount, from, to));
+                }
+            }
+            (stacks, moves)
+        });
+    }
+
+    fn count(hold: &Hold) -> usize {
+        return hold.iter().map(|stack| stack.len()).sum();
+    }
+
+    proptest! {
+        #[test]
+        fn parse_roundtrip((stacks, moves) in hold_and_moves()) {
+            let (hold, instructions) = parse(&render(&stacks, &moves)).unwrap();
+            prop_assert_eq!(hold, stacks);
+            let expected: Vec<Step> = moves
+                .iter()
+                .map(|(amount, from, to)| Step { amount: *amount, from: *from, to: *to })
+                .collect();
+            prop_assert_eq!(instructions, expected);
+        }
+
+        #[test]
+        fn cranes_preserve_crate_count((stacks, moves) in hold_and_moves()) {
+            let (hold, instructions) = parse(&render(&stacks, &moves)).unwrap();
+            let total = count(&hold);
+            let (mut hold_9000, mut hold_9001) = (hold.clone(), hold);
+            for step in instructions.iter() {
+                crate_mover_9000(&mut hold_9000, step).unwrap();
+                crate_mover_9001(&mut hold_9001, step).unwrap();
+                prop_assert_eq!(count(&hold_9000), total);
+                prop_assert_eq!(count(&hold_9001), total);
+            }
+        }
     }
 }
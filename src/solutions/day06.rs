@@ -1,48 +1,235 @@
+use crate::common::error::SolveError;
+use crate::common::limits;
+use crate::common::params::{self, Kind, Param};
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+pub type Input = Vec<char>;
+
+const PACKET_MARKER: Param = Param {
+    name: "packet_marker",
+    kind: Kind::Usize,
+    default: 4,
+    min: 1,
+    help: "distinct characters in a start-of-packet marker (part 1)",
+};
+
+const MESSAGE_MARKER: Param = Param {
+    name: "message_marker",
+    kind: Kind::Usize,
+    default: 14,
+    min: 1,
+    help: "distinct characters in a start-of-message marker (part 2)",
+};
+
+pub const PARAMS: &[Param] = &[PACKET_MARKER, MESSAGE_MARKER];
+
 // this could probably be done way more efficiently
 fn has_duplicates(slc: &[char]) -> bool {
     let mut chars = vec![' '; slc.len()];
     chars.copy_from_slice(slc);
     chars.sort();
-    let (_, dups) = chars.partition_dedup();
-    return !dups.is_empty();
+    #[cfg(feature = "nightly")]
+    return !chars.partition_dedup().1.is_empty();
+    #[cfg(not(feature = "nightly"))]
+    return chars.windows(2).any(|pair| pair[0] == pair[1]);
 }
 
-pub fn solve_p1(data: &str) -> usize {
-    let chars = data.chars().collect::<Vec<char>>();
-    let mut iter = chars.windows(4);
-    for i in 4.. {
-        if !has_duplicates(iter.next().unwrap()){
-            return i;
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    let data = data.trim_end();
+    if let Some(i) = data.find('\n') {
+        return Err(SolveError::parse(
+            1,
+            format!("expected a single line, found a line break at {}", i),
+        ));
+    }
+    return Ok(data.chars().collect());
+}
+
+fn find_marker(chars: &[char], size: usize) -> Result<usize, SolveError> {
+    let mut iter = chars.windows(size);
+    for i in size.. {
+        limits::check()?;
+        match iter.next() {
+            Some(window) if !has_duplicates(window) => return Ok(i),
+            Some(_) => continue,
+            None => break,
         }
     }
-    return 0;
+    return Err(SolveError::invalid(format!(
+        "no {} distinct characters in a row",
+        size
+    )));
 }
 
-pub fn solve_p2(data: &str) -> usize {
-    let chars = data.chars().collect::<Vec<char>>();
-    let mut iter = chars.windows(14);
-    for i in 14.. {
-        if !has_duplicates(iter.next().unwrap()){
-            return i;
+// Same as find_marker, but only ever holds on to the last `size` characters of the datastream.
+// Since re-sorting the window for every single character gets slow on huge streams, this keeps
+// count of how often every character occurs in the window instead. It counts bytes instead of
+// chars, which is the same thing for the ASCII the device sends.
+fn find_marker_stream(mut input: impl BufRead, size: usize) -> Result<usize, SolveError> {
+    let mut window = VecDeque::with_capacity(size + 1);
+    let mut counts = [0usize; 256];
+    let mut duplicates = 0;
+    let mut position = 0;
+    loop {
+        let buffer = input.fill_buf()?;
+        if buffer.is_empty() || buffer[0] == b'\n' {
+            break;
+        }
+        let length = buffer
+            .iter()
+            .position(|b| *b == b'\n')
+            .unwrap_or(buffer.len());
+        for byte in &buffer[..length] {
+            limits::check()?;
+            position += 1;
+            window.push_back(*byte);
+            counts[*byte as usize] += 1;
+            if counts[*byte as usize] == 2 {
+                duplicates += 1;
+            }
+            if window.len() > size {
+                let old = window.pop_front().unwrap() as usize;
+                if counts[old] == 2 {
+                    duplicates -= 1;
+                }
+                counts[old] -= 1;
+            }
+            if window.len() == size && duplicates == 0 {
+                return Ok(position);
+            }
         }
+        input.consume(length);
     }
-    return 0;
+    return Err(SolveError::invalid(format!(
+        "no {} distinct characters in a row",
+        size
+    )));
+}
+
+pub fn solve_p1(chars: &Input) -> Result<usize, SolveError> {
+    return find_marker(chars, params::get(&PACKET_MARKER)?);
+}
+
+pub fn solve_p2(chars: &Input) -> Result<usize, SolveError> {
+    return find_marker(chars, params::get(&MESSAGE_MARKER)?);
+}
+
+pub fn solve_p1_stream(input: impl BufRead) -> Result<usize, SolveError> {
+    return find_marker_stream(input, params::get(&PACKET_MARKER)?);
+}
+
+pub fn solve_p2_stream(input: impl BufRead) -> Result<usize, SolveError> {
+    return find_marker_stream(input, params::get(&MESSAGE_MARKER)?);
 }
 
 #[cfg(test)]
 mod test {
+    use super::parse;
     use super::solve_p1;
     use super::solve_p2;
+    use super::{solve_p1_stream, solve_p2_stream};
+    use crate::common::differential::{self, Rng};
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+
+    fn distinct(window: &[char]) -> bool {
+        return window.iter().collect::<HashSet<_>>().len() == window.len();
+    }
+
+    fn reference(data: &str, size: usize) -> usize {
+        let chars: Vec<char> = data.chars().collect();
+        return (size..=chars.len())
+            .find(|end| chars[end - size..*end].iter().collect::<HashSet<_>>().len() == size)
+            .unwrap();
+    }
+
+    fn reference_p1(data: &str) -> usize {
+        return reference(data, 4);
+    }
+
+    fn reference_p2(data: &str) -> usize {
+        return reference(data, 14);
+    }
+
+    // a noisy prefix from a small alphabet, which is then followed by a guaranteed marker
+    fn generate(rng: &mut Rng) -> String {
+        let alphabet = rng.range(2, 6) as u8;
+        let mut chars: Vec<char> = (0..rng.range(0, 60))
+            .map(|_| (b'a' + rng.below(alphabet as usize) as u8) as char)
+            .collect();
+        let mut marker: Vec<char> = ('a'..='z').collect();
+        rng.shuffle(&mut marker);
+        chars.extend(&marker[..14]);
+        chars.extend((0..rng.range(0, 20)).map(|_| (b'a' + rng.below(26) as u8) as char));
+        return chars.into_iter().collect();
+    }
 
     #[test]
     fn p1_test() {
         let data = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
-        assert_eq!(solve_p1(&data), 7);
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(7));
     }
 
     #[test]
     fn p2_test() {
         let data = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
-        assert_eq!(solve_p2(&data), 19);
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(19));
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    proptest! {
+        // the marker ends at the first window of distinct characters, and there is none before
+        #[test]
+        fn marker_is_first_distinct_window(prefix in "[a-e]{0,40}", suffix in "[a-z]{0,10}") {
+            let data = format!("{}abcdefghijklmn{}", prefix, suffix);
+            let chars: Vec<char> = data.chars().collect();
+            let input = parse(&data).unwrap();
+            let (p1, p2) = (solve_p1(&input).unwrap(), solve_p2(&input).unwrap());
+            for (size, end) in [(4, p1), (14, p2)] {
+                prop_assert!(distinct(&chars[end - size..end]));
+                prop_assert!(chars[..end - 1].windows(size).all(|w| !distinct(w)));
+            }
+            prop_assert!(p2 >= p1 + 10);
+        }
+    }
+
+    #[test]
+    fn p1_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p1_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p1(&input)),
+        );
+    }
+
+    #[test]
+    fn p2_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p2_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p2(&input)),
+        );
     }
 }
@@ -1,182 +1,369 @@
-use std::{
-    cell::RefCell,
-    cmp::min,
-    rc::{Rc, Weak},
-};
+use crate::common::error::SolveError;
+use crate::common::limits;
+use crate::common::params::{self, Kind, Param};
+use crate::common::parse;
+
+/// Index of a node in its `FileSystem`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
 
-// Fixed and modified version of this:
-// https://applied-math-coding.medium.com/a-tree-structure-implemented-in-rust-8344783abd75
-#[derive(Debug)]
-struct Node {
+#[derive(Debug, Clone)]
+pub struct Node {
     pub name: String,
+    /// the size of a file, or the total size of everything below a directory
     pub size: usize,
-    // Both the parent and the children are reference counted to make sure that they are only
-    // dropped when actually no body is referencing them anymore.
-    // The parent needs a weak reference to the parent so that dropping this node does not also
-    // attempt to drop the parent (which would result in an endless loop, because then the parent
-    // would try to drop the child it has a strong reference to, but then that child will, again,
-    // try to drop the parent, and so on...)
-    // Also, in both cases the nodes are wrapped in RefCells to enable inner mutability, since in
-    // my design for this solution I need to be able to change the size field after the fact. I
-    // could have done without it, if I measured directory size directly.
-    pub parent: Option<Weak<RefCell<Node>>>,
-    pub children: Vec<Rc<RefCell<Node>>>,
+    pub is_dir: bool,
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
+}
+
+/// The file system seen in the terminal session, with all nodes kept in one vector, and linked
+/// up by their indices; the root directory comes first.
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
 }
 
-impl Node {
-    pub fn new(name: String, size: usize) -> Self {
-        return Node {
-            name,
+const SMALL_DIRECTORY: Param = Param {
+    name: "small_directory",
+    kind: Kind::Usize,
+    default: 100_000,
+    min: 0,
+    help: "largest size of the directories that are summed up (part 1)",
+};
+
+const DISK_SIZE: Param = Param {
+    name: "disk_size",
+    kind: Kind::Usize,
+    default: 70_000_000,
+    min: 0,
+    help: "total space on the disk (part 2)",
+};
+
+const SPACE_NEEDED: Param = Param {
+    name: "space_needed",
+    kind: Kind::Usize,
+    default: 30_000_000,
+    min: 0,
+    help: "free space the update needs (part 2)",
+};
+
+pub const PARAMS: &[Param] = &[SMALL_DIRECTORY, DISK_SIZE, SPACE_NEEDED];
+
+impl FileSystem {
+    pub const ROOT: NodeId = NodeId(0);
+
+    pub fn new() -> Self {
+        return FileSystem {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                size: 0,
+                is_dir: true,
+                parent: None,
+                children: vec![],
+            }],
+        };
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        return &self.nodes[id.0];
+    }
+
+    pub fn root(&self) -> &Node {
+        return self.node(FileSystem::ROOT);
+    }
+
+    /// The first child of `parent` called `name`.
+    pub fn child(&self, parent: NodeId, name: &str) -> Option<NodeId> {
+        return self
+            .node(parent)
+            .children
+            .iter()
+            .copied()
+            .find(|child| self.node(*child).name == name);
+    }
+
+    /// Adds a file of `size`, or a directory, below `parent`; directory sizes are only up to date
+    /// after `update_sizes`.
+    pub fn add(&mut self, parent: NodeId, name: &str, size: usize, is_dir: bool) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            name: name.to_string(),
             size,
-            parent: None,
+            is_dir,
+            parent: Some(parent),
             children: vec![],
-        };
+        });
+        self.nodes[parent.0].children.push(id);
+        return id;
     }
 
-    pub fn add_child(&mut self, new_node: Rc<RefCell<Node>>) {
-        self.children.push(new_node);
+    /// All nodes below `from` and `from` itself, every node after everything below it.
+    pub fn post_order(&self, from: NodeId) -> Vec<NodeId> {
+        let mut order = vec![];
+        // the flag tells whether the children of a node are on the stack already
+        let mut stack = vec![(from, false)];
+        while let Some((id, expanded)) = stack.pop() {
+            if expanded {
+                order.push(id);
+                continue;
+            }
+            stack.push((id, true));
+            for child in self.node(id).children.iter().rev() {
+                stack.push((*child, false));
+            }
+        }
+        return order;
     }
 
-    pub fn measure_size(&mut self) -> usize {
-        for child in self.children.iter() {
-            self.size += child.borrow_mut().measure_size();
+    /// Sets the size of every directory to the total size of the files below it; fails if that
+    /// does not fit into a usize.
+    pub fn update_sizes(&mut self) -> Result<(), SolveError> {
+        for id in self.post_order(FileSystem::ROOT) {
+            if self.node(id).is_dir {
+                let children = &self.node(id).children;
+                let size = total_size(children.iter().map(|child| self.node(*child).size))?;
+                self.nodes[id.0].size = size;
+            }
         }
-        return self.size;
+        return Ok(());
     }
-}
 
-fn set_node_to_parent(node: &mut Rc<RefCell<Node>>) {
-    let parent = Rc::clone(&node.borrow().parent.as_ref().unwrap().upgrade().unwrap());
-    *node = parent;
+    pub fn directories(&self) -> impl Iterator<Item = &Node> {
+        return self.nodes.iter().filter(|node| node.is_dir);
+    }
 }
 
-fn set_node_to_child(node: &mut Rc<RefCell<Node>>, name: String) {
-    if let Some(child) = get_child_node(node, name) {
-        *node = child;
+impl Default for FileSystem {
+    fn default() -> Self {
+        return FileSystem::new();
     }
-    return;
 }
 
-fn get_child_node(parent: &Rc<RefCell<Node>>, name: String) -> Option<Rc<RefCell<Node>>> {
-    let children = &parent.borrow().children;
-    for i in 0..children.len() {
-        if children[i].borrow().name == name {
-            return Some(Rc::clone(&children[i]));
-        }
-    }
-    return None;
+pub type Input = FileSystem;
+
+fn total_size(sizes: impl IntoIterator<Item = usize>) -> Result<usize, SolveError> {
+    return sizes.into_iter().try_fold(0usize, |total, size| {
+        total.checked_add(size).ok_or_else(|| {
+            SolveError::invalid(format!("the sizes add up to more than {}", usize::MAX))
+        })
+    });
 }
 
-fn parse(data: &str) -> Rc<RefCell<Node>> {
-    let mut lines = data.lines().peekable();
-    assert_eq!(lines.next().unwrap(), "$ cd /");
-    let root_node = Rc::new(RefCell::new(Node::new("/".to_string(), 0)));
-    let mut current_node = Rc::clone(&root_node);
-
-    // loop annotation needed, because the ls block needs to be able to continue this loop from
-    // within a nested while loop
-    'core: while let Some(line) = lines.next() {
-        if let Some(dir) = line.strip_prefix("$ cd ") {
-            match dir {
-                ".." => set_node_to_parent(&mut current_node),
-                "/" => current_node = root_node.clone(),
-                _ => set_node_to_child(&mut current_node, dir.to_string()),
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    let mut lines = parse::Input::new(data).lines();
+    if lines.next().map(|line| line.text) != Some("$ cd /") {
+        return Err(SolveError::parse(
+            1,
+            "the session has to start with \"$ cd /\"",
+        ));
+    }
+    let mut fs = FileSystem::new();
+    let mut cwd = FileSystem::ROOT;
+    // whether the lines are the output of ls
+    let mut listing = false;
+    for line in lines {
+        limits::check()?;
+        if let Some(dir) = line.text.strip_prefix("$ cd ") {
+            cwd = match dir {
+                ".." => fs
+                    .node(cwd)
+                    .parent
+                    .ok_or_else(|| line.error("cannot leave the root directory"))?,
+                "/" => FileSystem::ROOT,
+                _ => fs
+                    .child(cwd, dir)
+                    .filter(|child| fs.node(*child).is_dir)
+                    .ok_or_else(|| {
+                        line.error(format!("no directory named {:?} listed here", dir))
+                    })?,
             };
-            continue 'core;
+            listing = false;
+        } else if line.text == "$ ls" {
+            listing = true;
+        } else if !listing || line.text.starts_with('$') {
+            return Err(line.error(format!("expected a command, got {:?}", line.text)));
+        } else if let Some(dir) = line.text.strip_prefix("dir ") {
+            fs.add(cwd, dir, 0, true);
+        } else {
+            // a file, e.g. "14848514 b.txt"
+            let mut scanner = line.scan();
+            let size = scanner.number()?;
+            scanner.literal(" ")?;
+            fs.add(cwd, scanner.rest(), size, false);
         }
+    }
+    // the session does not have to list a directory before all of its contents are known, so
+    // the directory sizes can only be added up in the end
+    fs.update_sizes()?;
+    return Ok(fs);
+}
+
+pub fn solve_p1(fs: &Input) -> Result<usize, SolveError> {
+    // the sum of the sizes of all directories of at most 100_000 (by default)
+    let max_size: usize = params::get(&SMALL_DIRECTORY)?;
+    let sizes = fs.directories().map(|dir| dir.size);
+    return total_size(sizes.filter(|size| *size <= max_size));
+}
+
+pub fn solve_p2(fs: &Input) -> Result<usize, SolveError> {
+    // the smallest directory that frees up enough space when it gets deleted
+    let used_space = fs.root().size;
+    let space_needed: usize = params::get(&SPACE_NEEDED)?;
+    let needed_space = params::get::<usize>(&DISK_SIZE)?
+        .checked_sub(used_space)
+        .and_then(|free_space| space_needed.checked_sub(free_space))
+        .ok_or_else(|| {
+            SolveError::invalid(format!(
+                "cannot free up space with {} in use on the disk",
+                used_space
+            ))
+        })?;
+    return fs
+        .directories()
+        .map(|dir| dir.size)
+        .filter(|size| *size >= needed_space)
+        .min()
+        .ok_or_else(|| {
+            SolveError::invalid(format!(
+                "no directory is as large as the {} needed",
+                needed_space
+            ))
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::solve_p1;
+    use super::solve_p2;
+    use super::{parse, FileSystem, NodeId};
+    use crate::common::differential::{self, Rng};
+    use crate::common::error::SolveError;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
 
-        if line == "$ ls" {
-            // another while loop that keeps peeking at the next line until that next line is
-            // another command instead of ls output. When that point is reached, go back to the
-            // core loop and iterate that
-            'ls_output: while let Some(next_line) = lines.peek() {
-                if next_line.starts_with("$") {
-                    continue 'core;
+    // size of every directory, keyed by its full path
+    fn directory_sizes(data: &str) -> HashMap<Vec<String>, usize> {
+        let mut sizes = HashMap::new();
+        let mut cwd: Vec<String> = vec![];
+        sizes.insert(vec![], 0);
+        for line in data.lines() {
+            let words: Vec<&str> = line.split(' ').collect();
+            match words[..] {
+                ["$", "cd", "/"] => cwd.clear(),
+                ["$", "cd", ".."] => {
+                    cwd.pop();
                 }
-                if let Some(new_dir) = next_line.strip_prefix("dir ") {
-                    current_node
-                        .borrow_mut()
-                        .add_child(Rc::new(RefCell::new(Node {
-                            name: new_dir.to_string(),
-                            size: 0,
-                            parent: Some(Rc::downgrade(&current_node)),
-                            children: vec![],
-                        })));
-                    lines.next();
-                    continue 'ls_output;
+                ["$", "cd", dir] => cwd.push(dir.to_string()),
+                ["$", "ls"] => {}
+                ["dir", dir] => {
+                    let mut path = cwd.clone();
+                    path.push(dir.to_string());
+                    sizes.entry(path).or_insert(0);
                 }
-                if let Some((size, name)) = next_line.split_once(" ") {
-                    current_node
-                        .borrow_mut()
-                        .add_child(Rc::new(RefCell::new(Node {
-                            name: name.to_string(),
-                            size: size.parse().unwrap(),
-                            parent: Some(Rc::downgrade(&current_node)),
-                            children: vec![],
-                        })));
+                [size, _] => {
+                    let size: usize = size.parse().unwrap();
+                    for depth in 0..=cwd.len() {
+                        *sizes.get_mut(&cwd[..depth]).unwrap() += size;
+                    }
                 }
-                lines.next();
+                _ => panic!("unexpected line {}", line),
             }
         }
+        return sizes;
     }
 
-    // Now that the dir structure is parsed, let's fix the recursive size values of each dir
-    // NOTE: I could have probably done this during the exploration / when parsing the commands,
-    // but since we cannot assume the traversel in the commands to be "well behaved" (no jumping
-    // around, apart from "cd .." chains), I decided to split this into two parts.
-    current_node = Rc::clone(&root_node);
-    current_node.borrow_mut().measure_size();
+    fn reference_p1(data: &str) -> usize {
+        return directory_sizes(data)
+            .values()
+            .filter(|size| **size <= 100_000)
+            .sum();
+    }
 
-    return root_node;
-}
+    fn reference_p2(data: &str) -> usize {
+        let sizes = directory_sizes(data);
+        let needed_space = 30_000_000 - (70_000_000 - sizes[&vec![]]);
+        return *sizes
+            .values()
+            .filter(|size| **size >= needed_space)
+            .min()
+            .unwrap();
+    }
 
-pub fn solve_p1(data: &str) -> usize {
-    // recursively iterate through each node that is not just a file and whose size is smaller
-    // than 100_000, and sum up the sizes of those nodes
-    fn f(fs: &Rc<RefCell<Node>>, sum: usize) -> usize {
-        return fs.borrow()
-            .children
-            .iter()
-            .fold(0, |acc, child| acc + f(child, sum))
-        // Make sure to only count directories; files do not have children, so just check whether
-        // the children field is empty
-        + if !fs.borrow().children.is_empty() && fs.borrow().size < 100_000 {
-            fs.borrow().size
-        } else {
-            0
-        } + sum;
+    #[derive(Debug)]
+    struct Dir {
+        files: Vec<(String, usize)>,
+        dirs: Vec<usize>,
+        name: String,
     }
 
-    let fs = parse(data);
-    return f(&fs, 0);
-}
+    fn transcript(dirs: &[Dir], current: usize, rng: &mut Rng, lines: &mut Vec<String>) {
+        let mut listing: Vec<String> = dirs[current]
+            .files
+            .iter()
+            .map(|(name, size)| format!("{} {}", size, name))
+            .chain(
+                dirs[current]
+                    .dirs
+                    .iter()
+                    .map(|d| format!("dir {}", dirs[*d].name)),
+            )
+            .collect();
+        rng.shuffle(&mut listing);
+        lines.push("$ ls".to_string());
+        lines.extend(listing);
+        for d in dirs[current].dirs.iter() {
+            lines.push(format!("$ cd {}", dirs[*d].name));
+            transcript(dirs, *d, rng, lines);
+            lines.push("$ cd ..".to_string());
+        }
+    }
 
-pub fn solve_p2(data: &str) -> usize {
-    // recursively iterate through each node that is not just a file and whose size is larger
-    // than the needed_space, and find the smallest among those
-    fn f(fs: &Rc<RefCell<Node>>, min_size: usize, needed_space: usize) -> usize {
-        return min(
-            fs.borrow().children.iter().fold(min_size, |acc, child| {
-                min(acc, f(child, min_size, needed_space))
-            }),
-            if !fs.borrow().children.is_empty() && fs.borrow().size > needed_space {
-                fs.borrow().size
+    // Builds a random file system whose total size lies between the 40_000_000 needed for part 2
+    // to make sense and the 70_000_000 capacity. Small files come in multiples of 5_000, so that
+    // directories regularly hit the 100_000 threshold of part 1 exactly.
+    fn generate(rng: &mut Rng) -> String {
+        let mut dirs = vec![Dir {
+            files: vec![],
+            dirs: vec![],
+            name: "/".to_string(),
+        }];
+        let mut depths = vec![0];
+        for k in 1..rng.range(1, 12) as usize {
+            let parent = rng.below(dirs.len());
+            if depths[parent] == 4 {
+                continue;
+            }
+            let id = dirs.len();
+            dirs[parent].dirs.push(id);
+            dirs.push(Dir {
+                files: vec![],
+                dirs: vec![],
+                name: format!("d{}", k),
+            });
+            depths.push(depths[parent] + 1);
+        }
+        let mut total = 0;
+        let mut k = 0;
+        while total <= 40_000_000 || k < 20 {
+            let size = if total > 40_000_000 || rng.below(4) > 0 {
+                rng.range(1, 20) as usize * 5_000
             } else {
-                usize::MAX
-            },
-        );
+                rng.range(1_000_000, 9_000_000) as usize
+            };
+            if total + size > 70_000_000 {
+                break;
+            }
+            let d = rng.below(dirs.len());
+            dirs[d].files.push((format!("f{}.txt", k), size));
+            total += size;
+            k += 1;
+        }
+        let mut lines = vec!["$ cd /".to_string()];
+        transcript(&dirs, 0, rng, &mut lines);
+        return lines.join("\n");
     }
 
-    let fs = parse(data);
-    let needed_space = 30_000_000 - (70_000_000 - fs.borrow().size);
-    return f(&fs, usize::MAX, needed_space);
-}
-
-#[cfg(test)]
-mod test {
-    use super::solve_p1;
-    use super::solve_p2;
-
     #[test]
     fn p1_test() {
         let data = "$ cd /
@@ -202,7 +389,7 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-        assert_eq!(solve_p1(&data), 95437);
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(95437));
     }
 
     #[test]
@@ -230,6 +417,109 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-        assert_eq!(solve_p2(&data), 24933642);
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(24933642));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("$ cd /\n$ cd ..").err(),
+            Some(SolveError::parse(2, "cannot leave the root directory"))
+        );
+        assert_eq!(
+            parse("$ cd /\n$ ls\ndir a\n$ cd b").err(),
+            Some(SolveError::parse(4, "no directory named \"b\" listed here"))
+        );
+        assert_eq!(
+            parse("$ ls").err(),
+            Some(SolveError::parse(
+                1,
+                "the session has to start with \"$ cd /\""
+            ))
+        );
+    }
+
+    #[test]
+    fn sizes_too_large() {
+        let overflow = SolveError::invalid(format!("the sizes add up to more than {}", usize::MAX));
+        let data = format!("$ cd /\n$ ls\n{} a\n1 b", usize::MAX);
+        assert_eq!(parse(&data).err(), Some(overflow));
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    // every directory is attached to one of the directories before it, and every file gets put
+    // into one of the directories
+    fn file_system() -> impl Strategy<Value = Vec<Dir>> {
+        let parents = prop::collection::vec(any::<prop::sample::Index>(), 0..10);
+        let files = prop::collection::vec((any::<prop::sample::Index>(), 1..300_000usize), 0..30);
+        return (parents, files).prop_map(|(parents, files)| {
+            let mut dirs = vec![Dir {
+                files: vec![],
+                dirs: vec![],
+                name: "/".to_string(),
+            }];
+            for (k, parent) in parents.iter().enumerate() {
+                let parent = parent.index(dirs.len());
+                let id = dirs.len();
+                dirs[parent].dirs.push(id);
+                dirs.push(Dir {
+                    files: vec![],
+                    dirs: vec![],
+                    name: format!("d{}", k),
+                });
+            }
+            for (k, (dir, size)) in files.iter().enumerate() {
+                let dir = dir.index(dirs.len());
+                dirs[dir].files.push((format!("f{}", k), *size));
+            }
+            dirs
+        });
+    }
+
+    fn sizes_add_up(fs: &FileSystem, id: NodeId) -> bool {
+        let node = fs.node(id);
+        if !node.is_dir {
+            return true;
+        }
+        let children: usize = node.children.iter().map(|child| fs.node(*child).size).sum();
+        return node.size == children && node.children.iter().all(|child| sizes_add_up(fs, *child));
+    }
+
+    proptest! {
+        #[test]
+        fn directory_sizes_add_up(dirs in file_system()) {
+            let mut lines = vec!["$ cd /".to_string()];
+            transcript(&dirs, 0, &mut Rng::new(0), &mut lines);
+            let mut fs = parse(&lines.join("\n")).unwrap();
+            let total: usize = dirs.iter().flat_map(|d| d.files.iter()).map(|(_, size)| size).sum();
+            prop_assert_eq!(fs.root().size, total);
+            prop_assert!(sizes_add_up(&fs, FileSystem::ROOT));
+            // the sizes are added up from scratch, so doing it again changes nothing
+            fs.update_sizes().unwrap();
+            prop_assert_eq!(fs.root().size, total);
+            let order = fs.post_order(FileSystem::ROOT);
+            let files: usize = dirs.iter().map(|d| d.files.len()).sum();
+            prop_assert_eq!(order.len(), dirs.len() + files);
+            prop_assert_eq!(order.last(), Some(&FileSystem::ROOT));
+        }
     }
 }
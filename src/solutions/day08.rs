@@ -1,139 +1,185 @@
-#[derive(Debug)]
-struct Tree {
+use crate::common::error::SolveError;
+use crate::common::geom::Direction;
+use crate::common::grid::Grid;
+use crate::common::image::{self, Image};
+use crate::common::limits;
+use crate::common::parse;
+
+#[derive(Debug, Clone)]
+pub struct Tree {
     pub height: i32,
     pub visible: bool,
 }
-type Forest = Vec<Vec<Tree>>;
-
-fn parse(data: &str) -> Forest {
-    data.lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| Tree {
-                    height: c.to_digit(10).unwrap() as i32,
-                    visible: false,
-                })
-                .collect()
-        })
-        .collect()
+pub type Forest = Grid<Tree>;
+pub type Input = Forest;
+
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    return parse::Input::new(data).grid(|c| match c.to_digit(10) {
+        Some(height) => Ok(Tree {
+            height: height as i32,
+            visible: false,
+        }),
+        None => Err(format!("{:?} is not a tree height", c)),
+    });
 }
 
-pub fn solve_p1(data: &str) -> usize {
-    let mut forest = parse(data);
-    let cols = forest.len();
-    let rows = forest[0].len();
-    let mut count = 2 * cols + 2 * rows - 4;
-    let mut tallest_height;
-    // rows looked at from the west
-    for j in 1..cols - 1 {
-        tallest_height = forest[j][0].height;
-        for i in 1..rows - 1 {
-            if !forest[j][i].visible && forest[j][i].height > tallest_height {
-                count += 1;
-                forest[j][i].visible = true;
-            }
-            tallest_height = tallest_height.max(forest[j][i].height);
-        }
-    }
-    // rows looked at from the east
-    for j in 1..cols - 1 {
-        tallest_height = forest[j][cols - 1].height;
-        for i in (1..rows - 1).rev() {
-            if !forest[j][i].visible && forest[j][i].height > tallest_height {
-                count += 1;
-                forest[j][i].visible = true;
+// the forest with every tree that can be seen from outside of it marked as visible
+fn mark_visible(forest: &Forest) -> Forest {
+    let mut forest = forest.clone();
+    let (rows, cols) = (forest.height(), forest.width());
+    // every row is looked at from the west and the east, and every column from the north and the
+    // south; the tree at the edge is always visible, and after that every tree that is taller
+    // than all the ones in front of it
+    let sweeps = (0..rows)
+        .flat_map(|row| {
+            [
+                ((row, 0), Direction::Right),
+                ((row, cols - 1), Direction::Left),
+            ]
+        })
+        .chain((0..cols).flat_map(|col| {
+            [
+                ((0, col), Direction::Down),
+                ((rows - 1, col), Direction::Up),
+            ]
+        }));
+    for (edge, direction) in sweeps {
+        forest[edge].visible = true;
+        let mut tallest_height = forest[edge].height;
+        for pos in forest.ray(edge, direction) {
+            if forest[pos].height > tallest_height {
+                forest[pos].visible = true;
+                tallest_height = forest[pos].height;
             }
-            tallest_height = tallest_height.max(forest[j][i].height);
         }
     }
-    // columns looked at from the north
-    for i in 1..rows - 1 {
-        tallest_height = forest[0][i].height;
-        for j in 1..cols - 1 {
-            if !forest[j][i].visible && forest[j][i].height > tallest_height {
-                count += 1;
-                forest[j][i].visible = true;
-            }
-            tallest_height = tallest_height.max(forest[j][i].height);
+    return forest;
+}
+
+pub fn solve_p1(forest: &Input) -> Result<usize, SolveError> {
+    return Ok(mark_visible(forest)
+        .iter()
+        .filter(|(_, tree)| tree.visible)
+        .count());
+}
+
+pub fn solve_p2(forest: &Input) -> Result<usize, SolveError> {
+    let mut best_score = 0;
+    for row in 0..forest.height() {
+        limits::check()?;
+        for col in 0..forest.width() {
+            let this_tree = forest[(row, col)].height;
+            // look into every direction until a tree is at least as tall as this one
+            let score: usize = Direction::ALL
+                .iter()
+                .map(|direction| {
+                    let mut distance = 0;
+                    for pos in forest.ray((row, col), *direction) {
+                        distance += 1;
+                        if forest[pos].height >= this_tree {
+                            break;
+                        }
+                    }
+                    distance
+                })
+                .product();
+            best_score = best_score.max(score);
         }
     }
-    // columns looked at from the south
-    for i in 1..rows - 1 {
-        tallest_height = forest[rows - 1][i].height;
-        for j in (1..cols - 1).rev() {
-            if !forest[j][i].visible && forest[j][i].height > tallest_height {
-                count += 1;
-                forest[j][i].visible = true;
-            }
-            tallest_height = tallest_height.max(forest[j][i].height);
+    return Ok(best_score);
+}
+
+/// The visibility map: visible trees in green and hidden ones in gray, brighter the taller they
+/// are.
+pub fn render(forest: &Input) -> Result<Image, SolveError> {
+    return Ok(Image::from_grid(&mark_visible(forest), |tree| {
+        let height = tree.height as usize;
+        match tree.visible {
+            true => image::shade([0, 60, 0], [120, 255, 120], height, 9),
+            false => image::shade([20, 20, 20], [110, 110, 110], height, 9),
         }
-    }
-    return count;
+    }));
 }
 
-pub fn solve_p2(data: &str) -> usize {
-    let forest = parse(data);
+#[cfg(test)]
+mod test {
+    use super::parse;
+    use super::solve_p1;
+    use super::solve_p2;
+    use crate::common::differential::{self, Rng};
+    use proptest::prelude::*;
 
-    let mut best_score = 0;
-    let mut north_score;
-    let mut south_score;
-    let mut west_score;
-    let mut east_score;
-
-    let cols = forest.len();
-    let rows = forest[0].len();
-
-    for row in 0..rows {
-        for col in 0..cols {
-            let this_tree = forest[row][col].height;
-
-            // look north
-            north_score = 0;
-            for i in (0..row).rev() {
-                north_score += 1;
-                if forest[i][col].height >= this_tree {
-                    break;
-                }
-            }
+    const DIRECTIONS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
 
-            // look south
-            south_score = 0;
-            for i in row + 1..rows {
-                south_score += 1;
-                if forest[i][col].height >= this_tree {
-                    break;
-                }
-            }
+    fn heights(data: &str) -> Vec<Vec<u32>> {
+        return data
+            .lines()
+            .map(|line| line.chars().map(|c| c.to_digit(10).unwrap()).collect())
+            .collect();
+    }
 
-            // look west
-            west_score = 0;
-            for j in (0..col).rev() {
-                west_score += 1;
-                if forest[row][j].height >= this_tree {
-                    break;
-                }
-            }
+    // all the trees between (row, col) and the edge in the given direction, nearest first
+    fn ray(forest: &[Vec<u32>], row: usize, col: usize, (dr, dc): (i32, i32)) -> Vec<u32> {
+        let mut trees = vec![];
+        let (mut r, mut c) = (row as i32 + dr, col as i32 + dc);
+        while r >= 0 && c >= 0 && (r as usize) < forest.len() && (c as usize) < forest[0].len() {
+            trees.push(forest[r as usize][c as usize]);
+            r += dr;
+            c += dc;
+        }
+        return trees;
+    }
 
-            // look east
-            east_score = 0;
-            for j in col + 1..cols {
-                east_score += 1;
-                if forest[row][j].height >= this_tree {
-                    break;
+    fn reference_p1(data: &str) -> usize {
+        let forest = heights(data);
+        let mut count = 0;
+        for row in 0..forest.len() {
+            for col in 0..forest[0].len() {
+                let height = forest[row][col];
+                if DIRECTIONS
+                    .iter()
+                    .any(|d| ray(&forest, row, col, *d).iter().all(|h| *h < height))
+                {
+                    count += 1;
                 }
             }
+        }
+        return count;
+    }
 
-            best_score = best_score.max(north_score * south_score * west_score * east_score);
+    fn reference_p2(data: &str) -> usize {
+        let forest = heights(data);
+        let mut best = 0;
+        for row in 0..forest.len() {
+            for col in 0..forest[0].len() {
+                let height = forest[row][col];
+                let score = DIRECTIONS
+                    .iter()
+                    .map(|d| {
+                        let trees = ray(&forest, row, col, *d);
+                        match trees.iter().position(|h| *h >= height) {
+                            Some(blocked) => blocked + 1,
+                            None => trees.len(),
+                        }
+                    })
+                    .product();
+                best = best.max(score);
+            }
         }
+        return best;
     }
-    // dbg!(&forest);
-    return best_score;
-}
 
-#[cfg(test)]
-mod test {
-    use super::solve_p1;
-    use super::solve_p2;
+    fn generate(rng: &mut Rng) -> String {
+        let (rows, cols) = (rng.range(1, 9), rng.range(1, 9));
+        return (0..rows)
+            .map(|_| {
+                (0..cols)
+                    .map(|_| rng.range(0, 9).to_string())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
 
     #[test]
     fn p1_test() {
@@ -142,7 +188,7 @@ mod test {
 65332
 33549
 35390";
-        assert_eq!(solve_p1(&data), 21);
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(21));
     }
 
     #[test]
@@ -152,6 +198,58 @@ mod test {
 65332
 33549
 35390";
-        assert_eq!(solve_p2(&data), 8);
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(8));
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn parse_roundtrip(
+            rows in prop::collection::vec(prop::collection::vec(0..10i32, 5), 1..6),
+        ) {
+            let data = rows
+                .iter()
+                .map(|row| row.iter().map(|h| h.to_string()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let forest = parse(&data).unwrap();
+            let heights: Vec<Vec<i32>> = forest
+                .rows()
+                .map(|row| row.iter().map(|tree| tree.height).collect())
+                .collect();
+            prop_assert_eq!(heights, rows);
+            prop_assert!(forest.iter().all(|(_, tree)| !tree.visible));
+        }
+
+        // the whole edge is always visible, and nothing more than the whole forest
+        #[test]
+        fn visible_count_bounded(size in 2..9usize, seed in any::<u64>()) {
+            let mut rng = Rng::new(seed);
+            let data = (0..size)
+                .map(|_| (0..size).map(|_| rng.range(0, 9).to_string()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let visible = solve_p1(&parse(&data).unwrap()).unwrap();
+            prop_assert!(visible >= 4 * size - 4 && visible <= size * size);
+        }
     }
 }
@@ -1,105 +1,153 @@
+use crate::common::error::SolveError;
+use crate::common::geom::{Direction, Point};
+use crate::common::limits;
+use crate::common::params::{self, Kind, Param};
+use crate::common::parse::{self, Line};
+use crate::common::stream::numbered_lines;
 use std::collections::HashSet;
+use std::io::BufRead;
 
-#[derive(Debug)]
-enum Command {
-    R(usize),
-    L(usize),
-    D(usize),
-    U(usize),
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    pub direction: Direction,
+    pub steps: usize,
 }
-type CommandList = Vec<Command>;
-
-fn parse(data: &str) -> CommandList {
-    return data
-        .lines()
-        .map(|line| {
-            if let Some((direction, steps)) = line.split_once(" ") {
-                return match direction {
-                    "R" => Command::R(steps.parse().unwrap()),
-                    "L" => Command::L(steps.parse().unwrap()),
-                    "U" => Command::U(steps.parse().unwrap()),
-                    "D" => Command::D(steps.parse().unwrap()),
-                    _ => panic!("wtf"),
-                };
-            };
-            panic!("wtf2");
-        })
-        .collect();
+pub type CommandList = Vec<Command>;
+pub type Input = CommandList;
+
+const SHORT_ROPE: Param = Param {
+    name: "short_rope",
+    kind: Kind::Usize,
+    default: 2,
+    min: 1,
+    help: "knots of the rope, including the head (part 1)",
+};
+
+const LONG_ROPE: Param = Param {
+    name: "long_rope",
+    kind: Kind::Usize,
+    default: 10,
+    min: 1,
+    help: "knots of the rope, including the head (part 2)",
+};
+
+pub const PARAMS: &[Param] = &[SHORT_ROPE, LONG_ROPE];
+
+// e.g. "R 4"
+fn parse_command(line: Line) -> Result<Command, SolveError> {
+    let mut scanner = line.scan();
+    let direction = scanner.word()?.parse().map_err(|e: String| line.error(e))?;
+    scanner.literal(" ")?;
+    let steps = scanner.number()?;
+    scanner.end()?;
+    return Ok(Command { direction, steps });
 }
 
-fn are_adjacent(head: &(i32, i32), tail: &(i32, i32)) -> bool {
-    head.0.abs_diff(tail.0) <= 1 && head.1.abs_diff(tail.1) <= 1
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    return parse::Input::new(data).lines().map(parse_command).collect();
 }
 
-fn move_rope<const S: usize>(rope: &mut [(i32, i32); S], set: &mut HashSet<(i32, i32)>) {
-    for i in 1..S {
-        if are_adjacent(&rope[i-1], &rope[i]) {
-            continue;
-        }
-        if rope[i-1].0 > rope[i].0 {
-            rope[i].0 = (rope[i-1].0 + rope[i].0).div_ceil(2);
-        } else {
-            rope[i].0 = (rope[i-1].0 + rope[i].0).div_floor(2);
-        }
-        if rope[i-1].1 > rope[i].1 {
-            rope[i].1 = (rope[i-1].1 + rope[i].1).div_ceil(2);
-        } else {
-            rope[i].1 = (rope[i-1].1 + rope[i].1).div_floor(2);
+// every knot that is no longer touching the one in front of it moves one step towards it
+fn move_rope(rope: &mut [Point], set: &mut HashSet<Point>) {
+    for i in 1..rope.len() {
+        if rope[i - 1].chebyshev(rope[i]) > 1 {
+            rope[i] = rope[i].step_towards(rope[i - 1]);
         }
     }
-    set.insert(rope.last().unwrap().clone());
+    set.insert(*rope.last().unwrap());
 }
 
-pub fn solve_p1(data: &str) -> usize {
-    solve::<2>(&data)
+pub fn solve_p1(commands: &Input) -> Result<usize, SolveError> {
+    return solve(params::get(&SHORT_ROPE)?, commands.iter().cloned().map(Ok));
 }
 
-pub fn solve_p2(data: &str) -> usize {
-    solve::<10>(&data)
+pub fn solve_p2(commands: &Input) -> Result<usize, SolveError> {
+    return solve(params::get(&LONG_ROPE)?, commands.iter().cloned().map(Ok));
 }
 
-fn solve<const S: usize>(data: &str) -> usize {
-    let commands = parse(data);
+pub fn solve_p1_stream(input: impl BufRead) -> Result<usize, SolveError> {
+    let commands = numbered_lines(input)
+        .map(|line| line.and_then(|(i, line)| parse_command(Line::new(i + 1, &line))));
+    return solve(params::get(&SHORT_ROPE)?, commands);
+}
+
+pub fn solve_p2_stream(input: impl BufRead) -> Result<usize, SolveError> {
+    let commands = numbered_lines(input)
+        .map(|line| line.and_then(|(i, line)| parse_command(Line::new(i + 1, &line))));
+    return solve(params::get(&LONG_ROPE)?, commands);
+}
+
+fn solve(
+    knots: usize,
+    commands: impl Iterator<Item = Result<Command, SolveError>>,
+) -> Result<usize, SolveError> {
     let mut set = HashSet::new();
-    let mut rope = &mut [(0, 0); S];
+    let mut rope = vec![Point::ORIGIN; knots];
 
-    set.insert(rope.last().unwrap().clone());
+    set.insert(*rope.last().unwrap());
 
-    for command in commands.iter() {
-        match command {
-            Command::R(steps) => {
-                for _ in 1..=*steps {
-                    rope[0] = (rope[0].0, rope[0].1 + 1);
-                    move_rope(&mut rope, &mut set);
-                }
-            }
-            Command::L(steps) => {
-                for _ in 1..=*steps {
-                    rope[0] = (rope[0].0, rope[0].1 - 1);
-                    move_rope(&mut rope, &mut set);
-                }
-            }
-            Command::U(steps) => {
-                for _ in 1..=*steps {
-                    rope[0] = (rope[0].0 + 1, rope[0].1);
-                    move_rope(&mut rope, &mut set);
-                }
-            }
-            Command::D(steps) => {
-                for _ in 1..=*steps {
-                    rope[0] = (rope[0].0 - 1, rope[0].1);
-                    move_rope(&mut rope, &mut set);
-                }
-            }
-        };
+    for command in commands {
+        let command = command?;
+        for _ in 1..=command.steps {
+            limits::check()?;
+            rope[0] += command.direction.unit();
+            move_rope(&mut rope, &mut set);
+        }
     }
-    return set.len();
+    return Ok(set.len());
 }
 
 #[cfg(test)]
 mod test {
     use super::solve_p1;
     use super::solve_p2;
+    use super::{move_rope, parse, Command};
+    use super::{solve_p1_stream, solve_p2_stream};
+    use crate::common::differential::{self, Rng};
+    use crate::common::geom::{Direction, Point};
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+
+    fn reference(data: &str, knots: usize) -> usize {
+        let mut rope = vec![(0i32, 0i32); knots];
+        let mut visited = HashSet::new();
+        visited.insert((0, 0));
+        for line in data.lines() {
+            let (direction, steps) = line.split_once(' ').unwrap();
+            let (dx, dy) = match direction {
+                "R" => (0, 1),
+                "L" => (0, -1),
+                "U" => (1, 0),
+                _ => (-1, 0),
+            };
+            for _ in 0..steps.parse::<usize>().unwrap() {
+                rope[0] = (rope[0].0 + dx, rope[0].1 + dy);
+                for i in 1..knots {
+                    let (x, y) = (rope[i - 1].0 - rope[i].0, rope[i - 1].1 - rope[i].1);
+                    if x.abs() > 1 || y.abs() > 1 {
+                        rope[i] = (rope[i].0 + x.signum(), rope[i].1 + y.signum());
+                    }
+                }
+                visited.insert(rope[knots - 1]);
+            }
+        }
+        return visited.len();
+    }
+
+    fn reference_p1(data: &str) -> usize {
+        return reference(data, 2);
+    }
+
+    fn reference_p2(data: &str) -> usize {
+        return reference(data, 10);
+    }
+
+    fn generate(rng: &mut Rng) -> String {
+        return (0..rng.range(1, 40))
+            .map(|_| format!("{} {}", rng.pick(&["R", "L", "U", "D"]), rng.range(1, 12)))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
 
     #[test]
     fn p1_test() {
@@ -111,7 +159,7 @@ R 4
 D 1
 L 5
 R 2";
-        assert_eq!(solve_p1(&data), 13);
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(13));
     }
 
     #[test]
@@ -124,6 +172,80 @@ R 17
 D 10
 L 25
 U 20";
-        assert_eq!(solve_p2(&data), 36);
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(36));
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn parse_roundtrip(commands in prop::collection::vec((0..4usize, 1..100usize), 0..30)) {
+            let data = commands
+                .iter()
+                .map(|(d, steps)| format!("{} {}", ["R", "L", "U", "D"][*d], steps))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let expected: Vec<Command> = commands
+                .iter()
+                .map(|(d, steps)| Command {
+                    direction: [Direction::Right, Direction::Left, Direction::Up, Direction::Down]
+                        [*d],
+                    steps: *steps,
+                })
+                .collect();
+            prop_assert_eq!(parse(&data), Ok(expected));
+        }
+
+        #[test]
+        fn knots_stay_adjacent(moves in prop::collection::vec(0..4usize, 0..200)) {
+            let mut rope = [Point::ORIGIN; 10];
+            let mut set = HashSet::new();
+            for m in moves {
+                rope[0] += Direction::ALL[m].unit();
+                move_rope(&mut rope, &mut set);
+                for i in 1..rope.len() {
+                    prop_assert!(rope[i - 1].chebyshev(rope[i]) <= 1);
+                }
+                prop_assert!(set.contains(&rope[9]));
+            }
+        }
+    }
+
+    #[test]
+    fn p1_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p1_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p1(&input)),
+        );
+    }
+
+    #[test]
+    fn p2_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p2_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p2(&input)),
+        );
     }
 }
@@ -1,51 +1,278 @@
-fn parse(data: &str) -> Vec<i32> {
+use crate::common::error::SolveError;
+use crate::common::image::{self, Image};
+use crate::common::limits;
+use crate::common::ocr;
+use crate::common::params::{self, Kind, Param};
+use crate::common::parse::Line;
+use crate::common::runner::Answer;
+use crate::common::stream::numbered_lines;
+use std::fmt;
+use std::io::BufRead;
+
+const FIRST_CYCLE: Param = Param {
+    name: "first_cycle",
+    kind: Kind::Usize,
+    default: 20,
+    min: 1,
+    help: "first cycle whose signal strength is summed up (part 1)",
+};
+
+const CYCLE_INTERVAL: Param = Param {
+    name: "cycle_interval",
+    kind: Kind::Usize,
+    default: 40,
+    min: 1,
+    help: "cycles between two summed up signal strengths (part 1)",
+};
+
+const SAMPLES: Param = Param {
+    name: "samples",
+    kind: Kind::Usize,
+    default: 6,
+    min: 0,
+    help: "how many signal strengths are summed up (part 1)",
+};
+
+pub const PARAMS: &[Param] = &[FIRST_CYCLE, CYCLE_INTERVAL, SAMPLES];
+
+// pixels in a row of the CRT
+const SCREEN_WIDTH: usize = 40;
+
+// runs the program, handing the value of X for every entry of the process to `record`
+fn execute<S: AsRef<str>>(
+    lines: impl Iterator<Item = Result<(usize, S), SolveError>>,
+    mut record: impl FnMut(i32),
+) -> Result<(), SolveError> {
     // just to make sure the that indexes overlap with the cycles needed for the solution
-    let mut process = vec![1];
-    let mut x = 1;
-    for line in data.lines() {
-        process.push(x);
-        if let Some(y) = line.strip_prefix("addx ") {
-            x += y.parse::<i32>().unwrap();
-            process.push(x);
+    record(1);
+    let mut x: i32 = 1;
+    for line in lines {
+        limits::check()?;
+        let (i, text) = line?;
+        let line = Line::new(i + 1, text.as_ref());
+        record(x);
+        if line.text.starts_with("addx ") {
+            let mut scanner = line.scan();
+            scanner.literal("addx ")?;
+            let y: i32 = scanner.number()?;
+            scanner.end()?;
+            x = x
+                .checked_add(y)
+                .ok_or_else(|| line.error("the X register overflows"))?;
+            record(x);
+        } else if line.text != "noop" {
+            return Err(line.error(format!("unknown instruction {:?}", line.text)));
         }
     }
-    return process;
+    return Ok(());
+}
+
+// the value of the X register during every cycle
+pub type Input = Vec<i32>;
+
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    let mut process = vec![];
+    execute(data.lines().enumerate().map(Ok), |x| process.push(x))?;
+    return Ok(process);
 }
 
-pub fn solve_p1(data: &str) -> i32 {
-    let process = parse(data);
-    return (0..=5)
-        .map(|z| {
-            let i = 20 + z * 40;
-            i as i32 * process[i - 1]
+fn too_short(entries: usize, needed: usize) -> SolveError {
+    return SolveError::invalid(format!(
+        "the program only runs for {} cycles, but {} are needed",
+        entries - 1,
+        needed
+    ));
+}
+
+// the cycles whose signal strengths make up part 1; 20, 60, .., 220 by default
+fn sampled_cycles() -> Result<Vec<usize>, SolveError> {
+    let first: usize = params::get(&FIRST_CYCLE)?;
+    let interval: usize = params::get(&CYCLE_INTERVAL)?;
+    let samples: usize = params::get(&SAMPLES)?;
+    return (0..samples)
+        .map(|k| {
+            k.checked_mul(interval)
+                .and_then(|offset| offset.checked_add(first))
         })
-        .sum();
+        .collect::<Option<_>>()
+        .ok_or_else(|| SolveError::invalid("the sampled cycles do not fit into a usize"));
+}
+
+fn draw_pixel(picture: &mut String, i: usize, x: i32) {
+    let position = i % SCREEN_WIDTH;
+    if position == 0 {
+        picture.push('\n');
+    }
+    let sprite = [x - 1, x, x + 1];
+    if sprite.contains(&(position as i32)) {
+        picture.push('#');
+    } else {
+        picture.push('.');
+    }
+}
+
+pub fn solve_p1(process: &Input) -> Result<i32, SolveError> {
+    let cycles = sampled_cycles()?;
+    let needed = cycles.last().copied().unwrap_or(0);
+    if process.len() < needed {
+        return Err(too_short(process.len(), needed));
+    }
+    return Ok(cycles.iter().map(|i| *i as i32 * process[i - 1]).sum());
+}
+
+/// What the CRT shows, and the letters on it if they can be made out; the answer is the letters,
+/// or else the picture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screen {
+    pub picture: String,
+    pub letters: Option<String>,
+}
+
+impl Screen {
+    fn new(picture: String) -> Self {
+        let letters = ocr::recognize(&picture);
+        return Screen { picture, letters };
+    }
+}
+
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match &self.letters {
+            Some(letters) => write!(f, "{}", letters),
+            None => write!(f, "{}", self.picture),
+        };
+    }
 }
 
-pub fn solve_p2(data: &str) -> String {
-    let xs = parse(data);
-    let mut sprite: [i32; 3];
+impl Answer for Screen {
+    fn picture(&self) -> Option<String> {
+        return Some(self.picture.trim_matches('\n').to_string());
+    }
+}
+
+pub fn solve_p2(xs: &Input) -> Result<Screen, SolveError> {
     let mut picture = "".to_string();
     for (i, x) in xs.iter().enumerate() {
-        let position = i % 40;
-        if position == 0 {
-            picture.push('\n');
+        draw_pixel(&mut picture, i, *x);
+    }
+    picture.pop();
+    return Ok(Screen::new(picture));
+}
+
+/// What the CRT shows, lit pixels in white on black.
+pub fn render(xs: &Input) -> Result<Image, SolveError> {
+    let screen = solve_p2(xs)?;
+    let rows: Vec<&str> = screen.picture.trim_start_matches('\n').lines().collect();
+    let mut picture = Image::new(SCREEN_WIDTH, rows.len(), image::BLACK);
+    for (row, line) in rows.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            if c == '#' {
+                picture.set((row, col), image::WHITE);
+            }
         }
-        sprite = [*x - 1, *x, *x + 1];
-        if sprite.contains(&(position as i32)) {
-            picture.push('#');
-        } else {
-            picture.push('.');
+    }
+    return Ok(picture);
+}
+
+pub fn solve_p1_stream(input: impl BufRead) -> Result<i32, SolveError> {
+    let cycles = sampled_cycles()?;
+    let needed = cycles.last().copied().unwrap_or(0);
+    let mut entries = 0;
+    let mut sampled = 0;
+    let mut signal = 0;
+    execute(numbered_lines(input), |x| {
+        entries += 1;
+        if cycles.get(sampled) == Some(&entries) {
+            signal += entries as i32 * x;
+            sampled += 1;
         }
+    })?;
+    if entries < needed {
+        return Err(too_short(entries, needed));
     }
+    return Ok(signal);
+}
+
+pub fn solve_p2_stream(input: impl BufRead) -> Result<Screen, SolveError> {
+    let mut picture = "".to_string();
+    let mut i = 0;
+    execute(numbered_lines(input), |x| {
+        draw_pixel(&mut picture, i, x);
+        i += 1;
+    })?;
     picture.pop();
-    return picture.to_string();
+    return Ok(Screen::new(picture));
 }
 
 #[cfg(test)]
 mod test {
+    use super::parse;
     use super::solve_p1;
     use super::solve_p2;
+    use super::{solve_p1_stream, solve_p2_stream};
+    use crate::common::differential::{self, Rng};
+    use proptest::prelude::*;
+
+    // the value of X during every cycle, starting with cycle 1
+    fn register_values(data: &str) -> Vec<i32> {
+        let mut x = 1;
+        let mut values = vec![];
+        for line in data.lines() {
+            match line.strip_prefix("addx ") {
+                Some(v) => {
+                    values.push(x);
+                    values.push(x);
+                    x += v.parse::<i32>().unwrap();
+                }
+                None => values.push(x),
+            }
+        }
+        return values;
+    }
+
+    fn reference_p1(data: &str) -> i32 {
+        let values = register_values(data);
+        return [20, 60, 100, 140, 180, 220]
+            .iter()
+            .map(|cycle| *cycle as i32 * values[cycle - 1])
+            .sum();
+    }
+
+    fn reference_p2(data: &str) -> String {
+        let values = register_values(data);
+        let rows: Vec<String> = values
+            .chunks(40)
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(col, x)| {
+                        if (x - col as i32).abs() <= 1 {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        return format!("\n{}\n", rows.join("\n"));
+    }
+
+    // a program that runs for exactly the 240 cycles the CRT needs
+    fn generate(rng: &mut Rng) -> String {
+        let mut cycles = 0;
+        let mut lines = vec![];
+        while cycles < 240 {
+            if cycles < 239 && rng.below(3) > 0 {
+                lines.push(format!("addx {}", rng.range(-15, 15)));
+                cycles += 2;
+            } else {
+                lines.push("noop".to_string());
+                cycles += 1;
+            }
+        }
+        return lines.join("\n");
+    }
 
     #[test]
     fn p1_test() {
@@ -195,7 +422,7 @@ addx -11
 noop
 noop
 noop";
-        assert_eq!(solve_p1(&data), 13140);
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(13140));
     }
 
     #[test]
@@ -346,15 +573,77 @@ addx -11
 noop
 noop
 noop";
+        let screen = solve_p2(&parse(data).unwrap()).unwrap();
+        assert_eq!(screen.letters, None);
         assert_eq!(
-            solve_p2(&data),
+            screen.picture,
             "\n##..##..##..##..##..##..##..##..##..##..
 ###...###...###...###...###...###...###.
 ####....####....####....####....####....
 #####.....#####.....#####.....#####.....
 ######......######......######......####
 #######.......#######.......#######.....\n"
-                .to_string()
+        );
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap().picture,
+            reference_p2,
+        );
+    }
+
+    proptest! {
+        // one entry per cycle on top of the initial value, ending with the sum of all the adds
+        #[test]
+        fn parse_tracks_every_cycle(
+            program in prop::collection::vec(prop::option::of(-20..20i32), 0..100),
+        ) {
+            let data = program
+                .iter()
+                .map(|instruction| match instruction {
+                    Some(v) => format!("addx {}", v),
+                    None => "noop".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let process = parse(&data).unwrap();
+            let cycles: usize = program.iter().map(|i| if i.is_some() { 2 } else { 1 }).sum();
+            prop_assert_eq!(process.len(), cycles + 1);
+            prop_assert_eq!(*process.last().unwrap(), 1 + program.iter().flatten().sum::<i32>());
+        }
+    }
+
+    #[test]
+    fn p1_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p1_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p1(&input)),
+        );
+    }
+
+    #[test]
+    fn p2_stream_matches() {
+        differential::check(
+            200,
+            generate,
+            |d| solve_p2_stream(d.as_bytes()),
+            |d| parse(d).and_then(|input| solve_p2(&input)),
         );
     }
 }
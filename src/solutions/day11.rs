@@ -1,104 +1,216 @@
-#[derive(Debug)]
-enum Operation {
+use crate::common::cycle::{self, Metric};
+use crate::common::error::SolveError;
+use crate::common::params::{self, Kind, Param};
+use crate::common::parse::{self, Line, Scanner};
+use crate::common::topk;
+
+const ROUNDS_P1: Param = Param {
+    name: "rounds_p1",
+    kind: Kind::Usize,
+    default: 20,
+    min: 0,
+    help: "rounds the monkeys play in part 1",
+};
+
+const ROUNDS_P2: Param = Param {
+    name: "rounds_p2",
+    kind: Kind::Usize,
+    default: 10_000,
+    min: 0,
+    help: "rounds the monkeys play in part 2",
+};
+
+const RELIEF: Param = Param {
+    name: "relief",
+    kind: Kind::U64,
+    default: 3,
+    min: 1,
+    help: "what worry levels are divided by after every inspection (part 1)",
+};
+
+pub const PARAMS: &[Param] = &[ROUNDS_P1, ROUNDS_P2, RELIEF];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
     Add(u64),
     Mul(u64),
     Sqr,
 }
 
-#[derive(Debug)]
-struct Monkey {
+#[derive(Debug, Clone)]
+pub struct Monkey {
     pub items: Vec<u64>,
     pub op: Operation,
     pub test_value: u64,
     pub throw_if_true_to: usize,
     pub throw_if_false_to: usize,
-    pub counter: usize,
 }
 
 impl Monkey {
-    pub fn inspect_items(&mut self, m: u64, divisor: u64) {
-        for item in self.items.iter_mut() {
-            *item = match self.op {
-                Operation::Add(x) => *item + x,
-                Operation::Mul(x) => *item * x,
-                Operation::Sqr => *item * *item,
-            }
-            .div_floor(divisor)
-                % m;
+    // the worry level of an item once this monkey is done with it; reducing it modulo m only
+    // commutes with the tests as long as nothing gets divided, so with relief we need to keep the
+    // exact values around, for as long as they fit into a u64
+    pub fn inspect(&self, item: u64, m: u64, divisor: u64) -> Result<u64, SolveError> {
+        // even the square of a u64 fits into a u128
+        let item = item as u128;
+        let worry = match self.op {
+            Operation::Add(x) => item + x as u128,
+            Operation::Mul(x) => item * x as u128,
+            Operation::Sqr => item * item,
+        };
+        if divisor == 1 {
+            return Ok((worry % m as u128) as u64);
         }
-        self.counter += self.items.len();
+        let worry = u64::try_from(worry).map_err(|_| {
+            SolveError::invalid(format!("the worry level of an item outgrows {}", u64::MAX))
+        })?;
+        #[cfg(feature = "nightly")]
+        return Ok(worry.div_floor(divisor));
+        #[cfg(not(feature = "nightly"))]
+        return Ok(worry / divisor);
     }
 
-    pub fn get_throw_index(&self) -> usize {
-        if self.items.first().unwrap() % self.test_value == 0 {
-            self.throw_if_true_to
-        } else {
-            self.throw_if_false_to
+    pub fn get_throw_index(&self, item: u64) -> usize {
+        if item.is_multiple_of(self.test_value) {
+            return self.throw_if_true_to;
         }
+        return self.throw_if_false_to;
     }
 }
 
-fn parse(data: &str) -> Vec<Monkey> {
-    data.split("\n\n")
-        .map(|monkey_str| {
-            let mut monkey_lines = monkey_str.lines();
-            let _ = monkey_lines.next().unwrap();
-            let items = monkey_lines
-                .next()
-                .unwrap()
-                .strip_prefix("  Starting items: ")
-                .unwrap()
-                .split(", ")
-                .map(|item| item.parse().unwrap())
-                .collect();
-            let op = match monkey_lines
-                .next()
-                .unwrap()
-                .strip_prefix("  Operation: new = old ")
-                .unwrap()
-                .split_once(" ")
-                .unwrap()
-            {
-                ("*", "old") => Operation::Sqr,
-                ("*", val) => Operation::Mul(val.parse().unwrap()),
-                ("+", val) => Operation::Add(val.parse().unwrap()),
-                _ => panic!("wtf"),
-            };
-            let test_value = monkey_lines
-                .next()
-                .unwrap()
-                .strip_prefix("  Test: divisible by ")
-                .unwrap()
-                .parse()
-                .unwrap();
-            let throw_if_true_to = monkey_lines
-                .next()
-                .unwrap()
-                .strip_prefix("    If true: throw to monkey ")
-                .unwrap()
-                .parse()
-                .unwrap();
-            let throw_if_false_to = monkey_lines
-                .next()
-                .unwrap()
-                .strip_prefix("    If false: throw to monkey ")
-                .unwrap()
-                .parse()
-                .unwrap();
-            return Monkey {
-                items,
-                op,
-                test_value,
-                throw_if_true_to,
-                throw_if_false_to,
-                counter: 0,
-            };
-        })
-        .collect()
+// the next line of a monkey's description, read up to the end of `prefix`
+fn next_field<'a>(
+    lines: &mut impl Iterator<Item = Line<'a>>,
+    end: usize,
+    prefix: &str,
+) -> Result<(Line<'a>, Scanner<'a>), SolveError> {
+    let Some(line) = lines.next() else {
+        return Err(SolveError::parse(
+            end,
+            format!("input ends before {:?}", prefix.trim()),
+        ));
+    };
+    let mut scanner = line.scan();
+    scanner.literal(prefix)?;
+    return Ok((line, scanner));
+}
+
+// a field that holds just one number, and the line it was found in
+fn number_field<'a>(
+    lines: &mut impl Iterator<Item = Line<'a>>,
+    end: usize,
+    prefix: &str,
+) -> Result<(usize, u64), SolveError> {
+    let (line, mut scanner) = next_field(lines, end, prefix)?;
+    let number = scanner.number()?;
+    scanner.end()?;
+    return Ok((line.number, number));
+}
+
+// one monkey, and the monkeys it throws to together with the lines they are named in
+fn parse_monkey(block: parse::Input) -> Result<(Monkey, [(usize, usize); 2]), SolveError> {
+    let end = block.end();
+    let mut lines = block.lines();
+
+    let (_, mut scanner) = next_field(&mut lines, end, "Monkey ")?;
+    scanner.number::<usize>()?;
+    scanner.literal(":")?;
+    scanner.end()?;
+
+    let (_, mut scanner) = next_field(&mut lines, end, "  Starting items: ")?;
+    let mut items = vec![];
+    while !scanner.at_end() {
+        if !items.is_empty() {
+            scanner.literal(", ")?;
+        }
+        items.push(scanner.number()?);
+    }
+
+    let (line, mut scanner) = next_field(&mut lines, end, "  Operation: new = old ")?;
+    let operator = scanner.word()?;
+    scanner.literal(" ")?;
+    let op = match operator {
+        "*" if scanner.optional("old") => Operation::Sqr,
+        "*" => Operation::Mul(scanner.number()?),
+        "+" => Operation::Add(scanner.number()?),
+        _ => return Err(line.error(format!("unknown operation {:?}", operator))),
+    };
+    scanner.end()?;
+
+    let (i, test_value) = number_field(&mut lines, end, "  Test: divisible by ")?;
+    if test_value == 0 {
+        return Err(SolveError::parse(i, "cannot test for divisibility by 0"));
+    }
+    let (i, throw_if_true_to) = number_field(&mut lines, end, "    If true: throw to monkey ")?;
+    let throw_if_true_to = (i, throw_if_true_to as usize);
+    let (i, throw_if_false_to) = number_field(&mut lines, end, "    If false: throw to monkey ")?;
+    let throw_if_false_to = (i, throw_if_false_to as usize);
+    if let Some(line) = lines.next() {
+        return Err(line.error("expected an empty line between two monkeys"));
+    }
+    let monkey = Monkey {
+        items,
+        op,
+        test_value,
+        throw_if_true_to: throw_if_true_to.1,
+        throw_if_false_to: throw_if_false_to.1,
+    };
+    return Ok((monkey, [throw_if_true_to, throw_if_false_to]));
+}
+
+pub type Input = Vec<Monkey>;
+
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    let mut blocks: Vec<parse::Input> = parse::Input::new(data).blocks().collect();
+    // an empty line at the very end, or no input at all
+    if blocks.last().is_some_and(|block| block.text().is_empty()) {
+        blocks.pop();
+    }
+    let monkeys = blocks
+        .into_iter()
+        .map(parse_monkey)
+        .collect::<Result<Vec<_>, _>>()?;
+    // only now we know how many monkeys there are to throw to
+    let n = monkeys.len();
+    return monkeys
+        .into_iter()
+        .map(
+            |(monkey, targets)| match targets.iter().find(|(_, t)| *t >= n) {
+                Some((i, t)) => Err(SolveError::parse(*i, format!("there is no monkey {}", t))),
+                None => Ok(monkey),
+            },
+        )
+        .collect();
 }
 
-fn solve(data: &str, rounds: i32, divisor: u64) -> usize {
-    let mut monkeys = parse(data);
+// An item at a monkey, with its worry level. Items never influence each other, so every item
+// can be followed around on its own.
+type Item = (usize, u64);
+
+// Where an item ends up after a round, and how often each monkey inspected it on the way; when
+// it is thrown to a monkey that has not had its turn yet, it gets inspected again in the same
+// round, otherwise it waits for the next one.
+fn play_round(
+    monkeys: &Input,
+    (monkey, item): Item,
+    m: u64,
+    divisor: u64,
+) -> Result<(Item, Vec<usize>), SolveError> {
+    let mut inspections = vec![0; monkeys.len()];
+    let (mut monkey, mut item) = (monkey, item);
+    loop {
+        inspections[monkey] += 1;
+        item = monkeys[monkey].inspect(item, m, divisor)?;
+        let target = monkeys[monkey].get_throw_index(item);
+        let thrown_back = target <= monkey;
+        monkey = target;
+        if thrown_back {
+            return Ok(((monkey, item), inspections));
+        }
+    }
+}
+
+fn solve(monkeys: &Input, rounds: usize, divisor: u64) -> Result<usize, SolveError> {
     // yay, modulo arithmatic...
     // This is the safety factor to keep the worry levels in check by modulo-ing each worry level
     // after its inspection by this value. Funnily enough, since the test_values seem to all be
@@ -106,42 +218,191 @@ fn solve(data: &str, rounds: i32, divisor: u64) -> usize {
     // but this is quicker to write down.
     let m = monkeys
         .iter()
-        .fold(1, |acc, monkey| acc * monkey.test_value);
+        .try_fold(1u64, |acc, monkey| acc.checked_mul(monkey.test_value))
+        .ok_or_else(|| {
+            SolveError::invalid(format!(
+                "the product of the test values outgrows {}",
+                u64::MAX
+            ))
+        })?;
 
-    for _ in 1..=rounds {
-        for i in 0..monkeys.len() {
-            monkeys[i].inspect_items(m, divisor);
-            for _ in 0..monkeys[i].items.len() {
-                let throw_index = monkeys[i].get_throw_index();
-                let item = monkeys[i].items.remove(0);
-                monkeys[throw_index].items.push(item);
-            }
+    // with the worry levels kept below m, every item comes back around to where it was at some
+    // point, so most of the rounds can be skipped
+    let mut inspections = vec![0; monkeys.len()];
+    for (i, monkey) in monkeys.iter().enumerate() {
+        for item in monkey.items.iter() {
+            let round = |item: &Item| play_round(monkeys, *item, m, divisor);
+            let (_, item_inspections) =
+                cycle::fast_forward((i, *item), rounds, round, |item| *item)?;
+            inspections.add(&item_inspections, 1);
         }
     }
-    let max_vals = monkeys.iter().fold((0, 0), |(max1, max2), monkey| {
-        if monkey.counter > max1 {
-            (monkey.counter, max1)
-        } else if monkey.counter > max2 {
-            (max1, monkey.counter)
-        } else {
-            (max1, max2)
-        }
-    });
-    return max_vals.0 * max_vals.1;
+    let most_active = topk::top_k(inspections, 2);
+    if most_active.len() < 2 {
+        return Err(SolveError::invalid(
+            "monkey business takes at least two monkeys",
+        ));
+    }
+    return Ok(most_active.iter().product());
 }
 
-pub fn solve_p1(data: &str) -> usize {
-    solve(data, 20, 3)
+pub fn solve_p1(monkeys: &Input) -> Result<usize, SolveError> {
+    return solve(monkeys, params::get(&ROUNDS_P1)?, params::get(&RELIEF)?);
 }
 
-pub fn solve_p2(data: &str) -> usize {
-    solve(data, 10_000, 1)
+pub fn solve_p2(monkeys: &Input) -> Result<usize, SolveError> {
+    return solve(monkeys, params::get(&ROUNDS_P2)?, 1);
 }
 
 #[cfg(test)]
 mod test {
     use super::solve_p1;
     use super::solve_p2;
+    use super::{parse, play_round, solve, Item, Operation};
+    use crate::common::differential::{self, Rng};
+    use crate::common::error::SolveError;
+    use proptest::prelude::*;
+    use std::collections::VecDeque;
+
+    struct Monkey {
+        items: VecDeque<u128>,
+        op: (String, String),
+        test: u128,
+        targets: (usize, usize),
+    }
+
+    fn reference_parse(data: &str) -> Vec<Monkey> {
+        return data
+            .split("\n\n")
+            .map(|block| {
+                let lines: Vec<&str> = block.lines().map(|l| l.trim()).collect();
+                let last_number = |line: &str| line.rsplit(' ').next().unwrap().parse().unwrap();
+                let op: Vec<&str> = lines[2].split(' ').collect();
+                Monkey {
+                    items: lines[1]
+                        .trim_start_matches("Starting items: ")
+                        .split(", ")
+                        .map(|item| item.parse().unwrap())
+                        .collect(),
+                    op: (op[4].to_string(), op[5].to_string()),
+                    test: last_number(lines[3]),
+                    targets: (
+                        last_number(lines[4]) as usize,
+                        last_number(lines[5]) as usize,
+                    ),
+                }
+            })
+            .collect();
+    }
+
+    // Plays one round, monkey after monkey, and returns how many items every monkey inspected.
+    // The worry levels are exact, unless `modulus` is given; returns None if they outgrow the u64
+    // the solver works with.
+    fn play_once(
+        monkeys: &mut [Monkey],
+        relief: u128,
+        modulus: Option<u128>,
+    ) -> Option<Vec<usize>> {
+        let mut inspections = vec![0; monkeys.len()];
+        for i in 0..monkeys.len() {
+            while let Some(old) = monkeys[i].items.pop_front() {
+                inspections[i] += 1;
+                let operand = match monkeys[i].op.1.as_str() {
+                    "old" => old,
+                    value => value.parse().unwrap(),
+                };
+                let mut new = match monkeys[i].op.0.as_str() {
+                    "+" => old + operand,
+                    _ => old.checked_mul(operand)?,
+                };
+                if new > u64::MAX as u128 {
+                    return None;
+                }
+                new /= relief;
+                if let Some(m) = modulus {
+                    new %= m;
+                }
+                let target = if new % monkeys[i].test == 0 {
+                    monkeys[i].targets.0
+                } else {
+                    monkeys[i].targets.1
+                };
+                monkeys[target].items.push_back(new);
+            }
+        }
+        return Some(inspections);
+    }
+
+    fn play(data: &str, rounds: usize, relief: u128, modulus: Option<u128>) -> Option<usize> {
+        let mut monkeys = reference_parse(data);
+        let mut inspections = vec![0; monkeys.len()];
+        for _ in 0..rounds {
+            let round = play_once(&mut monkeys, relief, modulus)?;
+            for (total, count) in inspections.iter_mut().zip(round) {
+                *total += count;
+            }
+        }
+        inspections.sort();
+        inspections.reverse();
+        return Some(inspections[0] * inspections[1]);
+    }
+
+    fn reference_p1(data: &str) -> usize {
+        return play(data, 20, 3, None).unwrap();
+    }
+
+    fn reference_p2(data: &str) -> usize {
+        fn gcd(a: u128, b: u128) -> u128 {
+            if b == 0 {
+                a
+            } else {
+                gcd(b, a % b)
+            }
+        }
+        let lcm = reference_parse(data)
+            .iter()
+            .fold(1, |acc, monkey| acc / gcd(acc, monkey.test) * monkey.test);
+        return play(data, 10_000, 1, Some(lcm)).unwrap();
+    }
+
+    fn render_monkey(i: usize, items: &str, op: &str, test: u64, t: usize, f: usize) -> String {
+        return format!(
+            "Monkey {}:\n  Starting items: {}\n  Operation: new = {}\
+             \n  Test: divisible by {}\n    If true: throw to monkey {}\
+             \n    If false: throw to monkey {}",
+            i, items, op, test, t, f
+        );
+    }
+
+    fn generate(rng: &mut Rng) -> String {
+        const PRIMES: [u64; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+        loop {
+            let n = rng.range(2, 8) as usize;
+            let mut tests = PRIMES.to_vec();
+            rng.shuffle(&mut tests);
+            let monkeys: Vec<String> = (0..n)
+                .map(|i| {
+                    let items: Vec<String> = (0..rng.range(1, 5))
+                        .map(|_| rng.range(1, 99).to_string())
+                        .collect();
+                    let op = match rng.below(3) {
+                        0 => "old * old".to_string(),
+                        1 => format!("old * {}", rng.range(2, 19)),
+                        _ => format!("old + {}", rng.range(1, 9)),
+                    };
+                    let mut others: Vec<usize> = (0..n).filter(|j| *j != i).collect();
+                    rng.shuffle(&mut others);
+                    let if_false = if n > 2 { others[1] } else { others[0] };
+                    render_monkey(i, &items.join(", "), &op, tests[i], others[0], if_false)
+                })
+                .collect();
+            let data = monkeys.join("\n\n");
+            // part 1 is checked against exact worry levels, so skip inputs where they explode
+            if play(&data, 20, 3, None).is_some() {
+                return data;
+            }
+        }
+    }
 
     #[test]
     fn p1_test() {
@@ -172,7 +433,7 @@ Monkey 3:
   Test: divisible by 17
     If true: throw to monkey 0
     If false: throw to monkey 1";
-        assert_eq!(solve_p1(&data), 10605);
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(10605));
     }
 
     #[test]
@@ -204,6 +465,159 @@ Monkey 3:
   Test: divisible by 17
     If true: throw to monkey 0
     If false: throw to monkey 1";
-        assert_eq!(solve_p2(&data), 2713310158);
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(2713310158));
+    }
+
+    #[test]
+    fn exact_worry_levels_overflow() {
+        let monkeys = parse(include_str!("../../data/examples/day11.txt")).unwrap();
+        let overflow =
+            SolveError::invalid(format!("the worry level of an item outgrows {}", u64::MAX));
+        assert_eq!(solve(&monkeys, 10_000, 3), Err(overflow));
+        assert_eq!(
+            play(
+                include_str!("../../data/examples/day11.txt"),
+                10_000,
+                3,
+                None
+            ),
+            None
+        );
+
+        // the test values alone are too much, even if the items never get there
+        let huge = [
+            render_monkey(0, "1", "old + 1", u64::MAX, 1, 1),
+            render_monkey(1, "1", "old + 1", 2, 0, 0),
+        ];
+        let huge = huge.join("\n\n");
+        let overflow = SolveError::invalid(format!(
+            "the product of the test values outgrows {}",
+            u64::MAX
+        ));
+        assert_eq!(solve(&parse(&huge).unwrap(), 20, 3), Err(overflow));
+    }
+
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            300,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    #[test]
+    fn p2_differential() {
+        differential::check(
+            20,
+            generate,
+            |d| solve_p2(&parse(d).unwrap()).unwrap(),
+            reference_p2,
+        );
+    }
+
+    // items, operation (0 = square, 1 = multiply, 2 = add) with its operand, test value, and the
+    // two targets, which get mapped onto the other monkeys
+    type RawMonkey = (
+        Vec<u64>,
+        (usize, u64),
+        u64,
+        (prop::sample::Index, prop::sample::Index),
+    );
+
+    fn monkeys() -> impl Strategy<Value = Vec<RawMonkey>> {
+        let monkey = (
+            prop::collection::vec(1..100u64, 1..6),
+            (0..3usize, 1..20u64),
+            prop::sample::select(vec![2u64, 3, 5, 7, 11, 13, 17, 19, 23]),
+            any::<(prop::sample::Index, prop::sample::Index)>(),
+        );
+        return prop::collection::vec(monkey, 2..8);
+    }
+
+    fn render(monkeys: &[RawMonkey]) -> String {
+        return monkeys
+            .iter()
+            .enumerate()
+            .map(|(i, (items, op, test, (t, f)))| {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                let op = match op {
+                    (0, _) => "old * old".to_string(),
+                    (1, x) => format!("old * {}", x),
+                    (_, x) => format!("old + {}", x),
+                };
+                let others: Vec<usize> = (0..monkeys.len()).filter(|j| *j != i).collect();
+                let (t, f) = (others[t.index(others.len())], others[f.index(others.len())]);
+                render_monkey(i, &items.join(", "), &op, *test, t, f)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+    }
+
+    proptest! {
+        #[test]
+        fn parse_roundtrip(raw in monkeys()) {
+            let monkeys = parse(&render(&raw)).unwrap();
+            prop_assert_eq!(monkeys.len(), raw.len());
+            for (monkey, (items, op, test, _)) in monkeys.iter().zip(raw.iter()) {
+                prop_assert_eq!(&monkey.items, items);
+                let expected = match op {
+                    (0, _) => Operation::Sqr,
+                    (1, x) => Operation::Mul(*x),
+                    (_, x) => Operation::Add(*x),
+                };
+                prop_assert_eq!(&monkey.op, &expected);
+                prop_assert_eq!(monkey.test_value, *test);
+            }
+        }
+
+        // Following every item on its own has to end up with the same items at the same monkeys,
+        // and the same number of inspections per monkey, as playing whole rounds the way the
+        // puzzle describes them. Played without relief, since the exact worry levels of part 1
+        // quickly outgrow u64 on arbitrary inputs.
+        #[test]
+        fn items_move_like_in_whole_rounds(raw in monkeys()) {
+            let data = render(&raw);
+            let monkeys = parse(&data).unwrap();
+            let m = monkeys.iter().fold(1, |acc, monkey| acc * monkey.test_value);
+            let mut reference = reference_parse(&data);
+            let mut items: Vec<Item> = monkeys
+                .iter()
+                .enumerate()
+                .flat_map(|(i, monkey)| monkey.items.iter().map(move |item| (i, *item)))
+                .collect();
+            for _ in 0..100 {
+                let expected = play_once(&mut reference, 1, Some(m as u128)).unwrap();
+                let mut inspections = vec![0; monkeys.len()];
+                let mut held = vec![vec![]; monkeys.len()];
+                for item in items.iter_mut() {
+                    let (next, item_inspections) = play_round(&monkeys, *item, m, 1).unwrap();
+                    for (total, count) in inspections.iter_mut().zip(item_inspections) {
+                        *total += count;
+                    }
+                    held[next.0].push(next.1 as u128);
+                    *item = next;
+                }
+                prop_assert_eq!(inspections, expected);
+                for (held, monkey) in held.iter_mut().zip(reference.iter()) {
+                    let mut expected: Vec<u128> = monkey.items.iter().copied().collect();
+                    expected.sort();
+                    held.sort();
+                    prop_assert_eq!(&*held, &expected);
+                }
+            }
+        }
+
+        // played without relief, for the same reason; the items have to come back around within
+        // those rounds to skip any
+        #[test]
+        fn skipping_rounds_changes_nothing(raw in monkeys(), rounds in 0..300usize) {
+            let data = render(&raw);
+            let monkeys = parse(&data).unwrap();
+            let m = monkeys.iter().fold(1, |acc, monkey| acc * monkey.test_value);
+            let expected = play(&data, rounds, 1, Some(m as u128)).unwrap();
+            prop_assert_eq!(solve(&monkeys, rounds, 1), Ok(expected));
+        }
     }
 }
@@ -1,139 +1,175 @@
-use std::collections::{BinaryHeap, HashSet};
+use crate::common::error::SolveError;
+use crate::common::geom::{Direction, Point};
+use crate::common::grid::Grid;
+use crate::common::image::{self, Image};
+use crate::common::parse;
+use crate::common::search::{self, Search};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-struct Node {
+pub struct Node {
     elevation: u8,
+    is_start: bool,
     is_end: bool,
-    cost: i32,
 }
 
-type Pos = (usize, usize);
-
-#[derive(Debug)]
-struct Edge {
-    node: Pos,
-    cost: i32,
-}
-
-impl Ord for Edge {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.cost.cmp(&self.cost)
+pub type Input = Grid<Node>;
+
+pub fn parse(data: &str) -> Result<Input, SolveError> {
+    let map = parse::Input::new(data).grid(|c| match c {
+        'S' => Ok(Node {
+            elevation: b'a',
+            is_start: true,
+            is_end: false,
+        }),
+        'E' => Ok(Node {
+            elevation: b'z',
+            is_start: false,
+            is_end: true,
+        }),
+        'a'..='z' => Ok(Node {
+            elevation: c as u8,
+            is_start: false,
+            is_end: false,
+        }),
+        _ => Err(format!("{:?} is not an elevation", c)),
+    })?;
+    let starts = map.iter().filter(|(_, node)| node.is_start).count();
+    let ends = map.iter().filter(|(_, node)| node.is_end).count();
+    if starts != 1 || ends != 1 {
+        return Err(SolveError::parse(
+            1,
+            format!(
+                "expected exactly one S and one E, found {} and {}",
+                starts, ends
+            ),
+        ));
     }
+    return Ok(map);
 }
 
-impl PartialOrd for Edge {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+// the neighbors that are at most one step up from point
+fn neighbors(point: Point, map: &Grid<Node>) -> impl Iterator<Item = Point> + '_ {
+    let elevation = map[point].elevation;
+    return Direction::ALL
+        .into_iter()
+        .map(move |direction| point + direction.unit())
+        .filter(
+            move |next| match next.to_pos().and_then(|pos| map.get(pos)) {
+                Some(node) => node.elevation - 1 <= elevation,
+                None => false,
+            },
+        );
 }
 
-impl PartialEq for Edge {
-    fn eq(&self, other: &Self) -> bool {
-        self.cost.eq(&other.cost)
-    }
+// the shortest ways from S, up to E
+fn climb(map: &Input) -> Result<Search<Point>, SolveError> {
+    // parse made sure that there is exactly one starting point
+    let start = Point::try_from(map.position(|node| node.is_start).unwrap())
+        .map_err(|_| SolveError::invalid("the heightmap is too large"))?;
+    return search::bfs(
+        [start],
+        |point| neighbors(*point, map),
+        |point| map[*point].is_end,
+    );
 }
 
-impl Eq for Edge {}
-
-fn parse(data: &str) -> Vec<Vec<Node>> {
-    data.lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| match c {
-                    'S' => Node {
-                        elevation: 'a' as u8,
-                        is_end: false,
-                        cost: 0,
-                    },
-                    'E' => Node {
-                        elevation: 'z' as u8 + 1,
-                        is_end: true,
-                        cost: i32::MAX,
-                    },
-                    'a'..='z' => Node {
-                        elevation: c as u8,
-                        is_end: false,
-                        cost: i32::MAX,
-                    },
-                    _ => panic!("wtf"),
-                })
-                .collect()
-        })
-        .collect()
+pub fn solve_p1(map: &Input) -> Result<usize, SolveError> {
+    let search = climb(map)?;
+    return Ok(search.goal().map_or(0, |(_, steps)| steps as usize));
 }
 
-fn update_neighbors((j, i): &(usize, usize), map: &Vec<Vec<Node>>, neighbors: &mut Vec<Pos>) {
-    neighbors.clear();
-    if *j > 0 && map[*j - 1][*i].elevation - 1 <= map[*j][*i].elevation {
-        neighbors.push((*j - 1, *i));
-    }
-    if *i > 0 && map[*j][*i - 1].elevation - 1 <= map[*j][*i].elevation {
-        neighbors.push((*j, *i - 1));
-    }
-    if *j < map.len() - 1 && map[*j + 1][*i].elevation - 1 <= map[*j][*i].elevation {
-        neighbors.push((*j + 1, *i));
-    }
-    if *i < map[0].len() - 1 && map[*j][*i + 1].elevation - 1 <= map[*j][*i].elevation {
-        neighbors.push((*j, *i + 1));
-    }
+pub fn solve_p2(_map: &Input) -> Result<usize, SolveError> {
+    return Ok(0);
 }
 
-pub fn solve_p1(data: &str) -> i32 {
-    let mut map = parse(data);
-    // find starting point
-    let (mut j, mut i) = (0, 0);
-    for jstart in 0..map.len() {
-        for istart in 0..map[0].len() {
-            if map[jstart][istart].cost == 0 {
-                (j, i) = (jstart, istart);
-            }
-        }
-    }
-
-    let mut visited = HashSet::new();
-    let mut queue = BinaryHeap::new();
-    let mut neighbors = Vec::with_capacity(4); // any node never has more than 4 neighbors
-
-    queue.push(Edge {
-        node: (j, i),
-        cost: 0,
+/// The heightmap in shades of gray, brighter the higher it is, with the shortest way from S to E
+/// in red if there is one.
+pub fn render(map: &Input) -> Result<Image, SolveError> {
+    let mut picture = Image::from_grid(map, |node| {
+        image::shade(
+            [30, 30, 30],
+            image::WHITE,
+            (node.elevation - b'a') as usize,
+            25,
+        )
     });
-
-    while let Some(Edge { node, cost }) = queue.pop() {
-        update_neighbors(&node, &map, &mut neighbors);
-        for neighbor in neighbors.iter() {
-            if visited.contains(neighbor) {
-                continue;
-            }
-            visited.insert(neighbor.clone());
-            let mut next_node = &mut map[neighbor.0][neighbor.1];
-            let new_cost = cost + 1;
-
-            if next_node.is_end {
-                return new_cost;
-            }
-
-            if new_cost < next_node.cost {
-                next_node.cost = new_cost;
-                queue.push(Edge {
-                    node: neighbor.clone(),
-                    cost: new_cost,
-                });
-            }
+    let search = climb(map)?;
+    if let Some((goal, _)) = search.goal() {
+        for point in search.path(goal).unwrap() {
+            picture.set(point.to_pos().unwrap(), [220, 30, 30]);
         }
     }
-
-    return 0;
-}
-
-pub fn solve_p2(data: &str) -> usize {
-    return 0;
+    return Ok(picture);
 }
 
 #[cfg(test)]
 mod test {
+    use super::parse;
     use super::solve_p1;
     use super::solve_p2;
+    use crate::common::differential::{self, Rng};
+    use proptest::prelude::*;
+    use std::collections::VecDeque;
+
+    // breadth first search from S; E has elevation z, and S has elevation a
+    fn reference_p1(data: &str) -> usize {
+        let map: Vec<Vec<char>> = data.lines().map(|line| line.chars().collect()).collect();
+        let elevation = |c: char| match c {
+            'S' => 'a' as i32,
+            'E' => 'z' as i32,
+            _ => c as i32,
+        };
+        let mut steps = vec![vec![None; map[0].len()]; map.len()];
+        let mut queue = VecDeque::new();
+        for (j, row) in map.iter().enumerate() {
+            for (i, c) in row.iter().enumerate() {
+                if *c == 'S' {
+                    steps[j][i] = Some(0);
+                    queue.push_back((j, i));
+                }
+            }
+        }
+        while let Some((j, i)) = queue.pop_front() {
+            if map[j][i] == 'E' {
+                return steps[j][i].unwrap();
+            }
+            for (dj, di) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (nj, ni) = (j as i32 + dj, i as i32 + di);
+                if nj < 0 || ni < 0 || nj as usize >= map.len() || ni as usize >= map[0].len() {
+                    continue;
+                }
+                let (nj, ni) = (nj as usize, ni as usize);
+                if steps[nj][ni].is_none() && elevation(map[nj][ni]) <= elevation(map[j][i]) + 1 {
+                    steps[nj][ni] = Some(steps[j][i].unwrap() + 1);
+                    queue.push_back((nj, ni));
+                }
+            }
+        }
+        return 0;
+    }
+
+    // an uphill slope from west to east, climbing roughly one letter per column plus some noise,
+    // so that E is reachable a good part of the time
+    fn generate(rng: &mut Rng) -> String {
+        let (height, width) = (rng.range(2, 6) as usize, rng.range(26, 40) as usize);
+        let mut map: Vec<Vec<char>> = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|i| {
+                        let slope = (i * 25 / (width - 1)) as i64;
+                        (b'a' + (slope + rng.range(-1, 1)).clamp(0, 25) as u8) as char
+                    })
+                    .collect()
+            })
+            .collect();
+        map[rng.below(height)][0] = 'S';
+        map[rng.below(height)][width - 1] = 'E';
+        return map
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
 
     #[test]
     fn p1_test() {
@@ -142,7 +178,7 @@ abcryxxl
 accszExk
 acctuvwj
 abdefghi";
-        assert_eq!(solve_p1(&data), 31);
+        assert_eq!(solve_p1(&parse(data).unwrap()), Ok(31));
     }
 
     #[test]
@@ -152,6 +188,54 @@ abcryxxl
 accszExk
 acctuvwj
 abdefghi";
-        assert_eq!(solve_p2(&data), 0);
+        assert_eq!(solve_p2(&parse(data).unwrap()), Ok(0));
+    }
+
+    // part 2 is not solved yet, so there is nothing to compare it against
+    #[test]
+    fn p1_differential() {
+        differential::check(
+            500,
+            generate,
+            |d| solve_p1(&parse(d).unwrap()).unwrap(),
+            reference_p1,
+        );
+    }
+
+    proptest! {
+        // S is the only start, sitting at height a, and E the only end, sitting at height z
+        #[test]
+        fn parse_marks_start_and_end(
+            rows in prop::collection::vec("[a-z]{8}", 1..6),
+            start in 0..48usize,
+            end in 0..48usize,
+        ) {
+            let mut chars: Vec<Vec<char>> = rows.iter().map(|r| r.chars().collect()).collect();
+            let (start, end) = (start % (8 * rows.len()), end % (8 * rows.len()));
+            prop_assume!(start != end);
+            chars[start / 8][start % 8] = 'S';
+            chars[end / 8][end % 8] = 'E';
+            let data = chars
+                .iter()
+                .map(|row| row.iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let map = parse(&data).unwrap();
+            prop_assert_eq!(map.height(), rows.len());
+            for (j, row) in map.rows().enumerate() {
+                prop_assert_eq!(row.len(), 8);
+                for (i, node) in row.iter().enumerate() {
+                    let pos = j * 8 + i;
+                    prop_assert_eq!(node.is_end, pos == end);
+                    prop_assert_eq!(node.is_start, pos == start);
+                    let expected = match chars[j][i] {
+                        'S' => b'a',
+                        'E' => b'z',
+                        c => c as u8,
+                    };
+                    prop_assert_eq!(node.elevation, expected);
+                }
+            }
+        }
     }
 }